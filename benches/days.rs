@@ -0,0 +1,110 @@
+use std::{fs, path::Path};
+
+use aoc2023::{
+    Day01, Day02, Day03, Day04, Day05, Day06, Day07, Day08, Day09, Day10, Day11, Solution,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// Benchmarks parsing `input_file`, then each of `parts` against the parsed input.
+///
+/// Parsing starts from the file already in memory, so disk reads stay out of the numbers.
+fn bench_input<S: Solution>(c: &mut Criterion, day: u8, input_file: &str, parts: &[u8]) {
+    let name = Path::new(input_file)
+        .file_stem()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned();
+    let input_str = fs::read_to_string(input_file).unwrap();
+
+    let mut group = c.benchmark_group(format!("day{:02}", day));
+    group.bench_function(BenchmarkId::new("parse", &name), |b| {
+        b.iter(|| S::parse_str(&input_str).unwrap())
+    });
+
+    let input = S::parse_str(&input_str).unwrap();
+    for &part in parts {
+        let solve = match part {
+            1 => S::part1,
+            _ => S::part2,
+        };
+        group.bench_function(BenchmarkId::new(format!("part{}", part), &name), |b| {
+            b.iter(|| solve(&input).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_day01(c: &mut Criterion) {
+    bench_input::<Day01>(c, 1, "input/day01_test01.txt", &[1]);
+    bench_input::<Day01>(c, 1, "input/day01_test02.txt", &[2]);
+    bench_input::<Day01>(c, 1, "input/day01.txt", &[1, 2]);
+}
+
+fn bench_day02(c: &mut Criterion) {
+    bench_input::<Day02>(c, 2, "input/day02_test01.txt", &[1, 2]);
+    bench_input::<Day02>(c, 2, "input/day02.txt", &[1, 2]);
+}
+
+fn bench_day03(c: &mut Criterion) {
+    bench_input::<Day03>(c, 3, "input/day03_test01.txt", &[1, 2]);
+    bench_input::<Day03>(c, 3, "input/day03.txt", &[1, 2]);
+}
+
+fn bench_day04(c: &mut Criterion) {
+    bench_input::<Day04>(c, 4, "input/day04_test01.txt", &[1, 2]);
+    bench_input::<Day04>(c, 4, "input/day04.txt", &[1, 2]);
+}
+
+fn bench_day05(c: &mut Criterion) {
+    bench_input::<Day05>(c, 5, "input/day05_test01.txt", &[1, 2]);
+    bench_input::<Day05>(c, 5, "input/day05.txt", &[1, 2]);
+}
+
+fn bench_day06(c: &mut Criterion) {
+    bench_input::<Day06>(c, 6, "input/day06_test01.txt", &[1, 2]);
+    bench_input::<Day06>(c, 6, "input/day06.txt", &[1, 2]);
+}
+
+fn bench_day07(c: &mut Criterion) {
+    bench_input::<Day07>(c, 7, "input/day07_test01.txt", &[1, 2]);
+    bench_input::<Day07>(c, 7, "input/day07.txt", &[1, 2]);
+}
+
+fn bench_day08(c: &mut Criterion) {
+    bench_input::<Day08>(c, 8, "input/day08_test01.txt", &[1]);
+    bench_input::<Day08>(c, 8, "input/day08_test03.txt", &[2]);
+    bench_input::<Day08>(c, 8, "input/day08.txt", &[1, 2]);
+}
+
+fn bench_day09(c: &mut Criterion) {
+    bench_input::<Day09>(c, 9, "input/day09_test03.txt", &[1, 2]);
+    bench_input::<Day09>(c, 9, "input/day09.txt", &[1, 2]);
+}
+
+fn bench_day10(c: &mut Criterion) {
+    bench_input::<Day10>(c, 10, "input/day10_test01.txt", &[1]);
+    bench_input::<Day10>(c, 10, "input/day10_test03.txt", &[2]);
+    bench_input::<Day10>(c, 10, "input/day10.txt", &[1, 2]);
+}
+
+fn bench_day11(c: &mut Criterion) {
+    bench_input::<Day11>(c, 11, "input/day11_test01.txt", &[1, 2]);
+    bench_input::<Day11>(c, 11, "input/day11.txt", &[1, 2]);
+}
+
+criterion_group!(
+    benches,
+    bench_day01,
+    bench_day02,
+    bench_day03,
+    bench_day04,
+    bench_day05,
+    bench_day06,
+    bench_day07,
+    bench_day08,
+    bench_day09,
+    bench_day10,
+    bench_day11
+);
+
+criterion_main!(benches);
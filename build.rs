@@ -0,0 +1,108 @@
+// Turns every entry of input/examples.toml into its own #[test], so each example passes or
+// fails by name. The tests are included by src/aoc/examples.rs, which is only compiled for tests,
+// and only when the manifest exists (it doesn't in a packaged crate).
+
+use std::{env, fmt::Write, fs, io, path::Path};
+
+use toml::{Table, Value};
+
+const MANIFEST: &str = "input/examples.toml";
+
+const OPTIONS: [&str; 3] = ["use_joker", "seeds_as_ranges", "expansion"];
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", MANIFEST);
+    println!("cargo:rustc-check-cfg=cfg(examples_manifest)");
+    let manifest = match fs::read_to_string(MANIFEST) {
+        Ok(manifest) => manifest,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            // Notice the manifest coming back, even with an older modification time
+            println!("cargo:rerun-if-changed=input");
+            return;
+        }
+        Err(err) => panic!("couldn't read {}: {}", MANIFEST, err),
+    };
+    let manifest: Table = manifest
+        .parse()
+        .unwrap_or_else(|err| panic!("couldn't parse {}: {}", MANIFEST, err));
+    let examples = match manifest.get("examples") {
+        Some(Value::Array(examples)) => examples,
+        _ => panic!("{}: expected an `examples` array", MANIFEST),
+    };
+
+    let mut tests = String::new();
+    for (index, example) in examples.iter().enumerate() {
+        let example = example
+            .as_table()
+            .unwrap_or_else(|| panic!("{}: example {} is not a table", MANIFEST, index + 1));
+        tests.push_str(&example_test(index + 1, example));
+    }
+
+    let out_file = Path::new(&env::var("OUT_DIR").unwrap()).join("examples.rs");
+    fs::write(out_file, tests).unwrap();
+    println!("cargo:rustc-cfg=examples_manifest");
+}
+
+fn example_test(number: usize, example: &Table) -> String {
+    let field = |name: &str| {
+        example
+            .get(name)
+            .unwrap_or_else(|| panic!("{}: example {} has no `{}`", MANIFEST, number, name))
+    };
+    for key in example.keys() {
+        if !["file", "part", "expected", "ignore"].contains(&key.as_str())
+            && !OPTIONS.contains(&key.as_str())
+        {
+            panic!("{}: example {} has unknown key `{}`", MANIFEST, number, key);
+        }
+    }
+
+    let file = field("file")
+        .as_str()
+        .unwrap_or_else(|| panic!("{}: example {} `file` is not a string", MANIFEST, number));
+    let stem = Path::new(file).file_stem().unwrap().to_string_lossy();
+    let day: u8 = stem
+        .strip_prefix("day")
+        .and_then(|rest| rest.get(..2))
+        .and_then(|day| day.parse().ok())
+        .unwrap_or_else(|| panic!("{}: `{}` is not named dayNN", MANIFEST, file));
+    let part = field("part")
+        .as_integer()
+        .unwrap_or_else(|| panic!("{}: example {} `part` is not a number", MANIFEST, number));
+    let expected = match field("expected") {
+        Value::String(expected) => expected.clone(),
+        Value::Integer(expected) => expected.to_string(),
+        _ => panic!(
+            "{}: example {} `expected` is not a string or number",
+            MANIFEST, number
+        ),
+    };
+
+    let mut name = format!("{}_part{}", stem, part);
+    let mut options = String::new();
+    for option in OPTIONS {
+        match example.get(option) {
+            Some(value) => {
+                write!(name, "_{}_{}", option, value).unwrap();
+                write!(options, "{}: Some({}), ", option, value).unwrap();
+            }
+            None => write!(options, "{}: None, ", option).unwrap(),
+        }
+    }
+
+    let ignore = match example.get("ignore") {
+        Some(reason) => format!("#[ignore = {}]\n", reason),
+        None => String::new(),
+    };
+
+    format!(
+        "#[test]\n{}fn {}() {{\n    run_example(&Example {{ file: {:?}, day: {}, part: {}, {}expected: {:?} }});\n}}\n\n",
+        ignore,
+        name.replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
+        file,
+        day,
+        part,
+        options,
+        expected
+    )
+}
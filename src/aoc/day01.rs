@@ -1,19 +1,45 @@
 // https://adventofcode.com/2023/day/1
 
-use super::utils::get_lines;
+use tracing::{debug, trace};
 
-fn get_sum_calibration_values(input_file: &str, digits_can_be_text: bool) -> u32 {
-    let lines = get_lines(input_file);
+use super::{Error, Result};
 
+solution!(Day01, u32);
+
+/// Sum of the calibration values, reading digits only.
+pub fn part1(input: &Input) -> Result<u32> {
+    sum_calibration_values(input, false)
+}
+
+/// Sum of the calibration values, reading spelled-out digits too.
+pub fn part2(input: &Input) -> Result<u32> {
+    sum_calibration_values(input, true)
+}
+
+/// The calibration document lines.
+#[derive(Debug)]
+pub struct Input {
+    pub lines: Vec<String>,
+}
+
+fn parse_lines(lines: Vec<String>) -> Result<Input> {
+    Ok(Input { lines })
+}
+
+fn sum_calibration_values(input: &Input, digits_can_be_text: bool) -> Result<u32> {
     let mut sum_calibration_values = 0;
 
-    for line in lines {
+    for (line_index, line) in input.lines.iter().enumerate() {
         if !line.is_empty() {
-            sum_calibration_values += get_calibration_value(&line, digits_can_be_text);
+            let calibration_value = get_calibration_value(line, digits_can_be_text)
+                .ok_or_else(|| Error::parse(line_index + 1, 1, "no digits in line"))?;
+            trace!(line, calibration_value);
+            sum_calibration_values += calibration_value;
         }
     }
 
-    sum_calibration_values
+    debug!(sum_calibration_values);
+    Ok(sum_calibration_values)
 }
 
 fn get_digit(line: &str, digits: &mut Vec<u32>, digits_can_be_text: bool) {
@@ -68,47 +94,32 @@ fn get_digits(line: &str, digits_can_be_text: bool) -> Vec<u32> {
     digits
 }
 
-fn get_calibration_value(line: &str, digits_can_be_text: bool) -> u32 {
+fn get_calibration_value(line: &str, digits_can_be_text: bool) -> Option<u32> {
     let digits = get_digits(line, digits_can_be_text);
 
-    let first_digit = digits.first();
-    let last_digit = digits.last();
-
-    let mut calibration_value: String = String::new();
+    let first_digit = digits.first()?;
+    let last_digit = digits.last()?;
 
-    calibration_value += &first_digit.ok_or("").unwrap().to_string();
-    calibration_value += &last_digit.ok_or("").unwrap().to_string();
-
-    calibration_value.parse().unwrap()
+    Some(first_digit * 10 + last_digit)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::aoc::{solve, Solution};
 
     #[test]
-    fn test_sum_calibration_values_test01() {
+    fn test_sum_calibration_values_no_digits() {
+        let err = solve(1, 1, "input/day01_test02.txt").unwrap_err();
         assert_eq!(
-            142,
-            get_sum_calibration_values("input/day01_test01.txt", false)
+            "input/day01_test02.txt:2:1: no digits in line",
+            err.to_string()
         );
     }
 
     #[test]
-    fn test_sum_calibration_values_test02() {
-        assert_eq!(
-            281,
-            get_sum_calibration_values("input/day01_test02.txt", true)
-        );
-    }
-
-    #[test]
-    fn test_sum_calibration_values_part01() {
-        assert_eq!(54390, get_sum_calibration_values("input/day01.txt", false));
-    }
-
-    #[test]
-    fn test_sum_calibration_values_part02() {
-        assert_eq!(54277, get_sum_calibration_values("input/day01.txt", true));
+    fn test_parse_str_inline() {
+        let input = Day01::parse_str("1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet\n").unwrap();
+        assert_eq!(142, Day01::part1(&input).unwrap());
     }
 }
@@ -1,69 +1,97 @@
 // https://adventofcode.com/2023/day/2
 
-use super::utils::get_lines;
+use tracing::trace;
 
-#[derive(Debug, PartialEq)]
-struct CubeCount {
-    red: u32,
-    green: u32,
-    blue: u32,
+use super::utils::{column, parse_num, pieces, words};
+use super::{Error, Result};
+
+solution!(Day02, u32);
+
+/// Sum of the IDs of games possible with 12 red, 13 green and 14 blue cubes.
+pub fn part1(input: &Input) -> Result<u32> {
+    Ok(sum_ids_of_valid_games(input))
+}
+
+/// Sum of the power of the fewest cubes each game needs.
+pub fn part2(input: &Input) -> Result<u32> {
+    Ok(sum_power_sets(input))
 }
 
+/// Cubes of each colour shown in one handful.
 #[derive(Debug, PartialEq)]
-struct Input {
-    games: Vec<(u32, Vec<CubeCount>)>,
+pub struct CubeCount {
+    pub red: u32,
+    pub green: u32,
+    pub blue: u32,
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
+/// Each game's ID and the handfuls shown.
+#[derive(Debug, PartialEq)]
+pub struct Input {
+    pub games: Vec<(u32, Vec<CubeCount>)>,
+}
 
+fn parse_lines(lines: Vec<String>) -> Result<Input> {
     let mut iter = lines.split(|e| e.is_empty());
 
-    Input {
-        games: parse_games(iter.next().unwrap().to_owned()),
-    }
+    Ok(Input {
+        games: parse_games(iter.next().unwrap_or_default().to_owned())?,
+    })
 }
 
-fn parse_games(games_lines: Vec<String>) -> Vec<(u32, Vec<CubeCount>)> {
+fn parse_games(games_lines: Vec<String>) -> Result<Vec<(u32, Vec<CubeCount>)>> {
     let mut games: Vec<(u32, Vec<CubeCount>)> = Vec::new();
 
-    for game_line in games_lines.into_iter() {
-        let game_split: Vec<&str> = game_line.split(':').collect();
-
-        let game_id_part = game_split.first().unwrap();
+    for (line_index, game_line) in games_lines.iter().enumerate() {
+        let colon = game_line
+            .find(':')
+            .ok_or_else(|| Error::parse(line_index + 1, 1, "expected `Game <id>: <sets>`"))?;
+        let (game_id_part, game_result_part) = (&game_line[..colon], &game_line[colon + 1..]);
 
-        let game_id: u32 = game_id_part
-            .split_whitespace()
-            .collect::<Vec<&str>>()
+        let game_id_str = words(game_id_part)
             .last()
-            .unwrap()
-            .parse()
-            .unwrap();
+            .ok_or_else(|| Error::parse(line_index + 1, 1, "missing game id"))?;
 
-        let game_result_part = game_split.last().unwrap();
-
-        let game_result_sets: Vec<&str> = game_result_part.split(';').collect();
+        let game_id: u32 = parse_num(line_index, game_line, game_id_str)?;
 
         let mut cube_counts: Vec<CubeCount> = Vec::new();
 
-        for game_result_set in game_result_sets {
+        for (set_offset, game_result_set) in pieces(game_result_part, &[';']) {
             let mut cube_count = CubeCount {
                 red: 0,
                 green: 0,
                 blue: 0,
             };
-            let game_result_cubes: Vec<&str> = game_result_set.split(',').collect();
-            for game_result_cube in game_result_cubes {
-                let game_result_cube_split: Vec<&str> =
-                    game_result_cube.split_whitespace().collect();
-                let game_result_cube_count: u32 =
-                    game_result_cube_split.first().unwrap().parse().unwrap();
-                let game_result_cube_color = game_result_cube_split.last().unwrap();
-                match *game_result_cube_color {
+            for (cube_offset, game_result_cube) in pieces(game_result_set, &[',']) {
+                let cube_offset = colon + 1 + set_offset + cube_offset;
+                let game_result_cube_split: Vec<(usize, &str)> = words(game_result_cube)
+                    .map(|(offset, word)| (cube_offset + offset, word))
+                    .collect();
+                let (game_result_cube_count, (color_offset, game_result_cube_color)) =
+                    match game_result_cube_split[..] {
+                        [count, color] => (parse_num::<u32>(line_index, game_line, count)?, color),
+                        _ => {
+                            return Err(Error::parse(
+                                line_index + 1,
+                                column(game_line, cube_offset),
+                                format!(
+                                    "expected `<count> <color>`, found `{}`",
+                                    game_result_cube.trim()
+                                ),
+                            ))
+                        }
+                    };
+                match game_result_cube_color {
                     "red" => cube_count.red = game_result_cube_count,
                     "green" => cube_count.green = game_result_cube_count,
                     "blue" => cube_count.blue = game_result_cube_count,
-                    _ => panic!("invalid game result"),
+                    _ => {
+                        return Err(Error::parse(
+                            line_index + 1,
+                            column(game_line, color_offset),
+                            format!("invalid cube color `{}`", game_result_cube_color),
+                        ))
+                    }
                 }
             }
             cube_counts.push(cube_count);
@@ -72,14 +100,13 @@ fn parse_games(games_lines: Vec<String>) -> Vec<(u32, Vec<CubeCount>)> {
         games.push((game_id, cube_counts));
     }
 
-    games
+    Ok(games)
 }
 
-fn get_sum_ids_of_valid_games(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
-    println!("{:?}", input);
+fn sum_ids_of_valid_games(input: &Input) -> u32 {
+    trace!(?input);
     let mut sum_ids: u32 = 0;
-    for (game_id, cube_counts) in input.games {
+    for (game_id, cube_counts) in &input.games {
         if possible_game(cube_counts) {
             sum_ids += game_id;
         }
@@ -87,7 +114,7 @@ fn get_sum_ids_of_valid_games(input_file: &str) -> u32 {
     sum_ids
 }
 
-fn possible_game(cube_counts: Vec<CubeCount>) -> bool {
+fn possible_game(cube_counts: &[CubeCount]) -> bool {
     let mut result = true;
     for cube_count in cube_counts {
         if cube_count.red > 12 {
@@ -103,11 +130,10 @@ fn possible_game(cube_counts: Vec<CubeCount>) -> bool {
     result
 }
 
-fn get_sum_power_sets(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
-    println!("{:?}", input);
+fn sum_power_sets(input: &Input) -> u32 {
+    trace!(?input);
     let mut sum_power_sets: u32 = 0;
-    for (_, cube_counts) in input.games {
+    for (_, cube_counts) in &input.games {
         let mut reds: Vec<u32> = Vec::new();
         let mut greens: Vec<u32> = Vec::new();
         let mut blues: Vec<u32> = Vec::new();
@@ -125,28 +151,13 @@ fn get_sum_power_sets(input_file: &str) -> u32 {
     sum_power_sets
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_sum_ids_of_valid_games_test01() {
-        assert_eq!(8, get_sum_ids_of_valid_games("input/day02_test01.txt"));
-    }
-    
-    #[test]
-    fn test_sum_ids_of_valid_games() {
-        assert_eq!(2369, get_sum_ids_of_valid_games("input/day02.txt"));
-    }
-
-    #[test]
-    fn test_sum_power_sets_test01() {
-        assert_eq!(2286, get_sum_power_sets("input/day02_test01.txt"));
-    }
-    
-    #[test]
-    fn test_sum_power_sets_values() {
-        assert_eq!(66363, get_sum_power_sets("input/day02.txt"));
+    fn test_parse_games_invalid_color() {
+        let err = parse_games(vec!["Game 1: 3 purple".to_string()]).unwrap_err();
+        assert_eq!("<input>:1:11: invalid cube color `purple`", err.to_string());
     }
 }
@@ -1,153 +1,146 @@
 // https://adventofcode.com/2023/day/4
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use super::utils::get_lines;
+use super::utils::{parse_num, pieces};
+use super::{Error, Result};
 
 use regex::Regex;
+use tracing::trace;
 
+solution!(Day04, u64);
+
+/// Total points of the scratchcards.
+pub fn part1(input: &Input) -> Result<u64> {
+    Ok(scratchcard_score(input))
+}
+
+/// Total scratchcards, counting every copy won.
+pub fn part2(input: &Input) -> Result<u64> {
+    Ok(total_scratchcards(input))
+}
+
+/// The winning numbers and the numbers you have on one scratchcard.
 #[derive(Debug, Default)]
-struct Game {
-    winning_nums: Vec<u32>,
-    nums: Vec<u32>,
+pub struct Game {
+    pub winning_nums: Vec<u32>,
+    pub nums: Vec<u32>,
 }
 
+/// The scratchcards, by card number.
 #[derive(Debug)]
-struct Input {
-    games: HashMap<u32, Game>,
+pub struct Input {
+    pub games: HashMap<u32, Game>,
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
-
-    Input {
-        games: parse_games(lines),
-    }
+fn parse_lines(lines: Vec<String>) -> Result<Input> {
+    Ok(Input {
+        games: parse_games(lines)?,
+    })
 }
 
-fn parse_games(game_lines: Vec<String>) -> HashMap<u32, Game> {
+fn parse_games(game_lines: Vec<String>) -> Result<HashMap<u32, Game>> {
     lazy_static! {
         static ref RE_GAME: Regex = Regex::new(r"\d+").unwrap();
     }
 
     let mut games: HashMap<u32, Game> = HashMap::new();
 
-    for game_line in game_lines {
-        let game_line_parts: Vec<&str> = game_line.split([':', '|']).collect();
-        assert!(game_line_parts.len() == 3);
-
-        let card_num: Vec<u32> = RE_GAME
-            .find_iter(game_line_parts[0])
-            .map(|m| m.as_str().parse::<u32>().unwrap())
-            .collect();
-
-        let winning_nums: Vec<u32> = RE_GAME
-            .find_iter(game_line_parts[1])
-            .map(|m| m.as_str().parse::<u32>().unwrap())
-            .collect();
-
-        let nums: Vec<u32> = RE_GAME
-            .find_iter(game_line_parts[2])
-            .map(|m| m.as_str().parse::<u32>().unwrap())
-            .collect();
+    for (line_index, game_line) in game_lines.iter().enumerate() {
+        let game_line_parts: Vec<(usize, &str)> = pieces(game_line, &[':', '|']).collect();
+        if game_line_parts.len() != 3 {
+            return Err(Error::parse(
+                line_index + 1,
+                1,
+                "expected `Card <id>: <winning numbers> | <numbers>`",
+            ));
+        }
 
-        let game = Game {
-            winning_nums,
-            nums,
+        let parse_nums = |(offset, part): (usize, &str)| -> Result<Vec<u32>> {
+            RE_GAME
+                .find_iter(part)
+                .map(|m| parse_num(line_index, game_line, (offset + m.start(), m.as_str())))
+                .collect()
         };
 
-        games.insert(*card_num.first().unwrap(), game);
-    }
+        let card_num: Vec<u32> = parse_nums(game_line_parts[0])?;
 
-    games
-}
+        let winning_nums: Vec<u32> = parse_nums(game_line_parts[1])?;
 
-fn get_scratchcard_score(input_file: &str) -> u32 {
-    let mut sum_scratchcard_points: u32 = 0;
+        let nums: Vec<u32> = parse_nums(game_line_parts[2])?;
 
-    let input = parse_input(input_file);
+        let game = Game { winning_nums, nums };
 
-    for (_, game) in input.games.into_iter() {
-        let mut game_score: u32 = 0;
-        for num in game.nums {
-            if game.winning_nums.contains(&num) {
-                if game_score == 0 {
-                    game_score = 1;
-                } else {
-                    game_score *= 2;
-                }
-            }
-        }
-        sum_scratchcard_points += game_score;
+        let card_num = card_num
+            .first()
+            .ok_or_else(|| Error::parse(line_index + 1, 1, "missing card number"))?;
+
+        games.insert(*card_num, game);
     }
 
-    sum_scratchcard_points
+    Ok(games)
 }
 
-fn get_total_scratchcards(input_file: &str) -> u32 {
-    let input = parse_input(input_file);
-
-    let mut total_scratchcards: u32 = input.games.len() as u32;
-
-    total_scratchcards +=
-        get_total_scratchcards_rec(input.games.keys().cloned().collect(), &input.games);
-
-    total_scratchcards
+fn scratchcard_score(input: &Input) -> u64 {
+    input
+        .games
+        .values()
+        .map(|game| match num_winning_nums(game) {
+            0 => 0,
+            winning_nums => 1 << (winning_nums - 1),
+        })
+        .sum()
 }
 
-fn get_total_scratchcards_rec(game_ids: Vec<u32>, all_games: &HashMap<u32, Game>) -> u32 {
-    let mut sorted_game_ids = game_ids.clone();
-    sorted_game_ids.sort();
-    //println!("get_total_scratchcards_rec");
-    let mut total_scratchcards: u32 = 0;
-    for &game_id in &sorted_game_ids {
-        //println!("game_id: {game_id}");
-        if let Some(game) = all_games.get(&game_id) {
-            let winning_nums = num_winning_nums(game);
-
-            //println!("winning_nums: {winning_nums}");
-            let won_game_ids: Vec<u32> = ((game_id + 1)..(game_id + 1 + winning_nums)).collect();
-            if !won_game_ids.is_empty() {
-                //println!("won_game_ids: {:?}", won_game_ids);
-                total_scratchcards += won_game_ids.len() as u32;
-                total_scratchcards += get_total_scratchcards_rec(won_game_ids, all_games);
+fn total_scratchcards(input: &Input) -> u64 {
+    let mut game_ids: Vec<u32> = input.games.keys().copied().collect();
+    game_ids.sort();
+
+    // Every card only wins copies of later cards, so by the time we reach a card we know how many
+    // copies of it we hold
+    let mut copies: HashMap<u32, u64> = game_ids.iter().map(|&game_id| (game_id, 1)).collect();
+    for &game_id in &game_ids {
+        let game_copies = copies[&game_id];
+        let winning_nums = num_winning_nums(&input.games[&game_id]);
+        trace!(game_id, game_copies, winning_nums);
+        for won_game_id in (game_id + 1)..=(game_id + winning_nums) {
+            if let Some(won_copies) = copies.get_mut(&won_game_id) {
+                *won_copies += game_copies;
             }
         }
     }
-    total_scratchcards
+
+    copies.values().sum()
 }
 
 fn num_winning_nums(game: &Game) -> u32 {
-    let mut winning_nums: u32 = 0;
-    for num in &game.nums {
-        if game.winning_nums.contains(num) {
-            winning_nums += 1;
-        }
-    }
-    winning_nums
+    let winning_nums: HashSet<u32> = game.winning_nums.iter().copied().collect();
+    game.nums
+        .iter()
+        .filter(|num| winning_nums.contains(num))
+        .count() as u32
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use itertools::Itertools;
 
     #[test]
-    fn test_get_scratchcard_score_test01() {
-        assert_eq!(13, get_scratchcard_score("input/day04_test01.txt"));
-    }
-
-    #[test]
-    fn test_get_scratchcard_score() {
-        assert_eq!(20667, get_scratchcard_score("input/day04.txt"));
-    }
-
-    #[test]
-    fn test_get_total_scratchcards_test01() {
-        assert_eq!(30, get_total_scratchcards("input/day04_test01.txt"));
-    }
-
-    #[test]
-    fn test_get_total_scratchcards() {
-        assert_eq!(5833065, get_total_scratchcards("input/day04.txt"));
+    fn test_total_scratchcards_overflows_u32() {
+        // Card n wins a copy of every later card, so there are 2^(n-1) of it
+        let num_cards: u32 = 33;
+        let lines = (1..=num_cards)
+            .map(|card_num| {
+                format!(
+                    "Card {}: {} | {}",
+                    card_num,
+                    (1..num_cards).join(" "),
+                    (1..=num_cards - card_num).join(" ")
+                )
+            })
+            .collect();
+        let input = parse_lines(lines).unwrap();
+        assert_eq!((1 << num_cards) - 1, total_scratchcards(&input));
     }
 }
@@ -1,9 +1,22 @@
 // https://adventofcode.com/2023/day/5
 
 use itertools::Itertools;
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use tracing::{debug, trace};
 
-use super::utils::get_lines;
+use super::utils::{parse_num, words};
+use super::{Error, Result};
+
+solution!(Day05, u64);
+
+/// Lowest location for any of the seeds.
+pub fn part1(input: &Input) -> Result<u64> {
+    lowest_location(input, false)
+}
+
+/// Lowest location for any seed in the seed ranges.
+pub fn part2(input: &Input) -> Result<u64> {
+    lowest_location(input, true)
+}
 
 enum ParseStage {
     Seeds,
@@ -16,45 +29,47 @@ enum ParseStage {
     HumidityToLocation,
 }
 
+/// The seeds, which part 2 reads as `(start, length)` pairs, and each
+/// `(destination, source, length)` map, in lookup order.
 #[derive(Debug, Default)]
-struct Almanac {
-    seeds: Vec<u64>,
-    seed_to_soil: Vec<(u64, u64, u64)>,
-    soil_to_fertilizer: Vec<(u64, u64, u64)>,
-    fertilizer_to_water: Vec<(u64, u64, u64)>,
-    water_to_light: Vec<(u64, u64, u64)>,
-    light_to_temperature: Vec<(u64, u64, u64)>,
-    temperature_to_humidity: Vec<(u64, u64, u64)>,
-    humidity_to_location: Vec<(u64, u64, u64)>,
+pub struct Almanac {
+    pub seeds: Vec<u64>,
+    pub seed_to_soil: Vec<(u64, u64, u64)>,
+    pub soil_to_fertilizer: Vec<(u64, u64, u64)>,
+    pub fertilizer_to_water: Vec<(u64, u64, u64)>,
+    pub water_to_light: Vec<(u64, u64, u64)>,
+    pub light_to_temperature: Vec<(u64, u64, u64)>,
+    pub temperature_to_humidity: Vec<(u64, u64, u64)>,
+    pub humidity_to_location: Vec<(u64, u64, u64)>,
 }
 
+/// The parsed almanac.
 #[derive(Debug)]
-struct Input {
-    almanac: Almanac,
+pub struct Input {
+    pub almanac: Almanac,
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
-
-    Input {
-        almanac: parse_almanac(lines),
-    }
+fn parse_lines(lines: Vec<String>) -> Result<Input> {
+    Ok(Input {
+        almanac: parse_almanac(lines)?,
+    })
 }
 
-fn parse_almanac(lines: Vec<String>) -> Almanac {
+fn parse_almanac(lines: Vec<String>) -> Result<Almanac> {
     let mut parse_stage: ParseStage = ParseStage::Seeds;
     let mut almanac = Almanac::default();
     let mut parse_nums;
-    for almanac_line in lines {
+    for (line_index, almanac_line) in lines.iter().enumerate() {
         let split_line: Vec<&str> = almanac_line.split(':').collect();
         match split_line[0] {
             "seeds" => {
                 parse_stage = ParseStage::Seeds;
-                let seeds: Vec<u64> = split_line[1]
-                    .trim()
-                    .split(' ')
-                    .map(|seed| seed.parse().unwrap())
-                    .collect();
+                let seeds_offset = split_line[0].len() + 1;
+                let seeds: Vec<u64> = words(split_line.get(1).copied().unwrap_or_default())
+                    .map(|(offset, seed)| {
+                        parse_num(line_index, almanac_line, (seeds_offset + offset, seed))
+                    })
+                    .collect::<Result<_>>()?;
                 almanac.seeds = seeds;
                 continue;
             }
@@ -95,12 +110,18 @@ fn parse_almanac(lines: Vec<String>) -> Almanac {
         }
 
         if parse_nums {
-            let nums: (u64, u64, u64) = split_line[0]
-                .trim()
-                .split(' ')
-                .map(|seed| seed.parse().unwrap())
+            let nums: (u64, u64, u64) = words(split_line[0])
+                .map(|num| parse_num(line_index, almanac_line, num))
+                .collect::<Result<Vec<u64>>>()?
+                .into_iter()
                 .collect_tuple()
-                .unwrap();
+                .ok_or_else(|| {
+                    Error::parse(
+                        line_index + 1,
+                        1,
+                        "expected `<destination start> <source start> <length>`",
+                    )
+                })?;
 
             match parse_stage {
                 ParseStage::Seeds => (),
@@ -128,76 +149,118 @@ fn parse_almanac(lines: Vec<String>) -> Almanac {
             }
         }
     }
-    almanac
+    Ok(almanac)
 }
 
-fn get_lowest_location(input_file: &str, seeds_as_ranges: bool) -> u64 {
-    let input = parse_input(input_file);
-
-    let mut seeds: Vec<u64> = vec![];
+impl Almanac {
+    /// The maps from seed to location, in lookup order.
+    fn maps(&self) -> [&Vec<(u64, u64, u64)>; 7] {
+        [
+            &self.seed_to_soil,
+            &self.soil_to_fertilizer,
+            &self.fertilizer_to_water,
+            &self.water_to_light,
+            &self.light_to_temperature,
+            &self.temperature_to_humidity,
+            &self.humidity_to_location,
+        ]
+    }
+}
 
-    if seeds_as_ranges {
-        input.almanac.seeds.chunks(2).for_each(|range| {
-            let range = range[0]..(range[0] + range[1]);
-            seeds.append(&mut range.collect_vec());
-        });
+pub(crate) fn lowest_location(input: &Input, seeds_as_ranges: bool) -> Result<u64> {
+    let almanac = &input.almanac;
+    let lowest_location = if seeds_as_ranges {
+        if let [unpaired] = almanac.seeds.chunks(2).last().unwrap_or_default() {
+            return Err(Error::NoSolution(format!(
+                "seed `{}` has no range length",
+                unpaired
+            )));
+        }
+        lowest_range_location(almanac)
     } else {
-        seeds = input.almanac.seeds;
-    }
+        almanac
+            .seeds
+            .iter()
+            .map(|&seed| seed_location(almanac, seed))
+            .min()
+    };
+    debug!(?lowest_location);
+    lowest_location.ok_or_else(|| Error::NoSolution("the almanac lists no seeds".to_string()))
+}
 
-    let locations: Vec<_> = seeds
-        .par_iter()
-        .map(|seed| {
-            let soil_lookup = match get_destinations(*seed, &input.almanac.seed_to_soil) {
-                Some(lookup_val) => lookup_val,
-                _ => *seed,
-            };
-
-            let fertilizer_lookup =
-                match get_destinations(soil_lookup, &input.almanac.soil_to_fertilizer) {
-                    Some(lookup_val) => lookup_val,
-                    _ => soil_lookup,
-                };
-
-            let water_lookup =
-                match get_destinations(fertilizer_lookup, &input.almanac.fertilizer_to_water) {
-                    Some(lookup_val) => lookup_val,
-                    _ => fertilizer_lookup,
-                };
-
-            let light_lookup = match get_destinations(water_lookup, &input.almanac.water_to_light) {
-                Some(lookup_val) => lookup_val,
-                _ => water_lookup,
-            };
-
-            let temperature_lookup =
-                match get_destinations(light_lookup, &input.almanac.light_to_temperature) {
-                    Some(lookup_val) => lookup_val,
-                    _ => light_lookup,
-                };
-
-            let humidity_lookup = match get_destinations(
-                temperature_lookup,
-                &input.almanac.temperature_to_humidity,
-            ) {
-                Some(lookup_val) => lookup_val,
-                _ => temperature_lookup,
-            };
-
-            match get_destinations(humidity_lookup, &input.almanac.humidity_to_location) {
-                Some(lookup_val) => lookup_val,
-                _ => humidity_lookup,
-            }
+fn seed_location(almanac: &Almanac, seed: u64) -> u64 {
+    let location = almanac.maps().into_iter().fold(seed, |lookup_val, ranges| {
+        get_destinations(lookup_val, ranges).unwrap_or(lookup_val)
+    });
+    trace!(seed, location);
+    location
+}
+
+/// Pushes each `(start, length)` seed range through the maps as a whole, splitting it wherever a
+/// map's source ranges begin or end, so the work depends on the number of ranges rather than seeds.
+fn lowest_range_location(almanac: &Almanac) -> Option<u64> {
+    let seed_intervals: Vec<(u64, u64)> = almanac
+        .seeds
+        .chunks_exact(2)
+        .filter_map(|range| match range {
+            [start, length] if *length > 0 => Some((*start, *length)),
+            _ => None,
         })
         .collect();
 
-    *locations.iter().min().unwrap()
+    almanac
+        .maps()
+        .into_iter()
+        .fold(seed_intervals, |intervals, ranges| {
+            let dest_intervals = get_destination_intervals(intervals, ranges);
+            debug!(intervals = dest_intervals.len());
+            dest_intervals
+        })
+        .into_iter()
+        .map(|(start, _)| start)
+        .min()
+}
+
+fn get_destination_intervals(
+    mut intervals: Vec<(u64, u64)>,
+    ranges: &Vec<(u64, u64, u64)>,
+) -> Vec<(u64, u64)> {
+    let mut dest_intervals: Vec<(u64, u64)> = vec![];
+    for &(dest, source, length) in ranges {
+        let source_end = source + length;
+        let mut unmapped: Vec<(u64, u64)> = vec![];
+        for (start, interval_length) in intervals {
+            let end = start + interval_length;
+            let overlap_start = start.max(source);
+            let overlap_end = end.min(source_end);
+            if overlap_start >= overlap_end {
+                unmapped.push((start, interval_length));
+                continue;
+            }
+            trace!(
+                interval = ?(start, interval_length),
+                range = ?(dest, source, length),
+                overlap = ?(overlap_start, overlap_end - overlap_start)
+            );
+            dest_intervals.push((dest + (overlap_start - source), overlap_end - overlap_start));
+            if start < overlap_start {
+                unmapped.push((start, overlap_start - start));
+            }
+            if overlap_end < end {
+                unmapped.push((overlap_end, end - overlap_end));
+            }
+        }
+        intervals = unmapped;
+    }
+    // Anything no range covers keeps its number
+    dest_intervals.extend(intervals);
+    dest_intervals
 }
 
 fn get_destinations(lookup_val: u64, ranges: &Vec<(u64, u64, u64)>) -> Option<u64> {
     for range in ranges {
         if let Some(dest_val) = get_destination(lookup_val, range) {
-            return Some(dest_val)
+            return Some(dest_val);
         }
     }
     Some(lookup_val)
@@ -255,28 +318,45 @@ mod tests {
         assert_eq!(Some(13), get_destinations(13, &range));
     }
 
-    #[test]
-    fn test_get_lowest_location_test01() {
-        assert_eq!(35, get_lowest_location("input/day05_test01.txt", false));
-    }
-
-    #[test]
-    fn test_get_lowest_location_test02() {
-        assert_eq!(13, get_lowest_location("input/day05_test02.txt", false));
+    fn brute_force_lowest_range_location(almanac: &Almanac) -> Option<u64> {
+        almanac
+            .seeds
+            .chunks(2)
+            .flat_map(|range| range[0]..(range[0] + range[1]))
+            .map(|seed| seed_location(almanac, seed))
+            .min()
     }
 
     #[test]
-    fn test_get_lowest_location_score() {
-        assert_eq!(388071289, get_lowest_location("input/day05.txt", false));
+    fn test_get_destination_intervals() {
+        let range = vec![(50, 98, 2), (52, 50, 48)];
+        assert_eq!(
+            vec![(50, 2), (52, 48), (40, 10), (100, 5)],
+            get_destination_intervals(vec![(40, 65)], &range)
+        );
     }
 
     #[test]
-    fn test_get_lowest_location_seeds_as_ranges_test01() {
-        assert_eq!(46, get_lowest_location("input/day05_test01.txt", true));
+    fn test_lowest_range_location_matches_brute_force() {
+        for file in ["input/day05_test01.txt", "input/day05_test02.txt"] {
+            let input = parse_file(file).unwrap();
+            assert_eq!(
+                brute_force_lowest_range_location(&input.almanac),
+                lowest_range_location(&input.almanac),
+                "{}",
+                file
+            );
+        }
     }
 
     #[test]
-    fn test_get_lowest_location_seeds_as_ranges() {
-        assert_eq!(84206669, get_lowest_location("input/day05.txt", true));
+    fn test_unpaired_seed() {
+        let input = parse("seeds: 79 14 55\n\nseed-to-soil map:\n50 98 2\n").unwrap();
+        assert_eq!(14, part1(&input).unwrap());
+        let err = part2(&input).unwrap_err();
+        assert_eq!(
+            "no solution: seed `55` has no range length",
+            err.to_string()
+        );
     }
 }
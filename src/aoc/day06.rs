@@ -1,96 +1,250 @@
 // https://adventofcode.com/2023/day/6
 
-use super::utils::get_lines;
+use tracing::{debug, trace};
 
+use super::utils::{column, parse_num, words};
+use super::{Error, Result};
+
+solution!(Day06, u128);
+
+/// Product of the ways to beat each race's record.
+pub fn part1(input: &Input) -> Result<u128> {
+    multiple_beaten_records(&input.races)
+}
+
+/// Ways to beat the record of the single kerned race.
+pub fn part2(input: &Input) -> Result<u128> {
+    multiple_beaten_records(&input.kerned_races)
+}
+
+/// Each race's `(time, record distance)`.
 #[derive(Debug, Default)]
-struct Races {
-    races: Vec<(u64, u64)>,
+pub struct Races {
+    pub races: Vec<(u128, u128)>,
 }
 
+/// The races, read both as separate races and, with the spaces between the digits ignored, as one
+/// kerned race.
 #[derive(Debug)]
-struct Input {
-    races: Races,
+pub struct Input {
+    pub races: Races,
+    pub kerned_races: Races,
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
+fn parse_lines(lines: Vec<String>) -> Result<Input> {
+    Ok(Input {
+        races: parse_races(&lines, false)?,
+        kerned_races: parse_races(&lines, true)?,
+    })
+}
 
-    Input {
-        races: parse_races(lines),
+/// With `kerning`, every digit group on a line is part of the same number.
+fn parse_races(lines: &[String], kerning: bool) -> Result<Races> {
+    let mut races = Races::default();
+    let times = parse_race_line(lines, 0, "Time", kerning)?;
+    let distances = parse_race_line(lines, 1, "Distance", kerning)?;
+    if times.len() != distances.len() {
+        return Err(Error::parse(
+            2,
+            1,
+            format!(
+                "expected {} distances to match the times, found {}",
+                times.len(),
+                distances.len()
+            ),
+        ));
     }
+    races.races = times.into_iter().zip(distances).collect();
+    Ok(races)
 }
 
-fn parse_races(lines: Vec<String>) -> Races {
-    let mut races = Races::default();
-    let times_str = &lines[0].rsplit_once(':').unwrap().1.trim();
-    let times: Vec<u64> = times_str
-        .split_whitespace()
-        .map(|time| time.parse().unwrap())
-        .collect();
-    let distances_str = &lines[1].rsplit_once(':').unwrap().1.trim();
-    let distances: Vec<u64> = distances_str
-        .split_whitespace()
-        .map(|time| time.parse().unwrap())
-        .collect();
-    races.races = times
-        .into_iter()
-        .zip(distances)
-        .map(|(time, distance)| (time, distance))
+fn parse_race_line(
+    lines: &[String],
+    line_index: usize,
+    label: &str,
+    kerning: bool,
+) -> Result<Vec<u128>> {
+    let line = lines
+        .get(line_index)
+        .ok_or_else(|| Error::parse(line_index + 1, 1, format!("missing `{}:` line", label)))?;
+    let colon = line.find(':').ok_or_else(|| {
+        Error::parse(
+            line_index + 1,
+            1,
+            format!("expected `{}:` followed by numbers", label),
+        )
+    })?;
+    let found = line[..colon].trim();
+    if found != label {
+        return Err(Error::parse(
+            line_index + 1,
+            1,
+            format!("expected `{}:`, found `{}:`", label, found),
+        ));
+    }
+    let num_words: Vec<(usize, &str)> = words(&line[colon + 1..])
+        .map(|(offset, num)| (colon + 1 + offset, num))
         .collect();
-    races
+    let nums: Vec<u128> = num_words
+        .iter()
+        .map(|&num| parse_num(line_index, line, num))
+        .collect::<Result<_>>()?;
+    if !kerning || nums.is_empty() {
+        return Ok(nums);
+    }
+    let kerned: String = num_words.iter().map(|&(_, num)| num).collect();
+    let kerned = kerned.parse().map_err(|_| {
+        Error::parse(
+            line_index + 1,
+            column(line, num_words[0].0),
+            format!("kerned number `{}` is too large", kerned),
+        )
+    })?;
+    Ok(vec![kerned])
 }
 
-fn get_multiple_beaten_records(input_file: &str) -> u64 {
-    let mut beaten_records: u64 = 1;
-    let input = parse_input(input_file);
-    for race in input.races.races {
-        beaten_records *= get_beaten_records(race);
+fn multiple_beaten_records(races: &Races) -> Result<u128> {
+    let product = races.races.iter().try_fold(1u128, |product, &race| {
+        product
+            .checked_mul(get_beaten_records(race)?)
+            .ok_or_else(|| {
+                Error::NoSolution("the product of the ways to win is too large".to_string())
+            })
+    })?;
+    debug!(product);
+    Ok(product)
+}
+
+/// The number of hold times that go further than `distance`.
+///
+/// Holding for `t` goes `t * (time - t)`, so the winning hold times lie strictly between the roots
+/// of `t^2 - time * t + distance`, which are symmetric about `time / 2`.
+fn get_beaten_records((time, distance): (u128, u128)) -> Result<u128> {
+    let too_long = || Error::NoSolution(format!("the {} ms race is too long to solve", time));
+    let discriminant = match time
+        .checked_mul(time)
+        .ok_or_else(too_long)?
+        .checked_sub(distance.checked_mul(4).ok_or_else(too_long)?)
+    {
+        Some(discriminant) if discriminant > 0 => discriminant,
+        _ => return Ok(0),
+    };
+
+    // The integer square root can leave the lower root out by one either way, so nudge the first
+    // winning hold time until it's exact
+    let beats = |t: u128| t * (time - t) > distance;
+    let mut first = (time - isqrt(discriminant).min(time)) / 2;
+    while first <= time / 2 && !beats(first) {
+        first += 1;
     }
-    beaten_records
+    while first > 0 && beats(first - 1) {
+        first -= 1;
+    }
+
+    let beaten_records = if first > time / 2 {
+        0
+    } else {
+        time - 2 * first + 1
+    };
+    trace!(time, distance, first, beaten_records);
+    Ok(beaten_records)
 }
 
-fn get_beaten_records((time, distance): (u64, u64)) -> u64 {
-    let mut beaten_records: u64 = 0;
-    for t in 0..time {
-        let dist = t * (time - t);
-        if dist > distance {
-            beaten_records += 1;
+/// The largest `root` with `root * root <= n`.
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // Newton's method from above converges on the floor of the root
+    let mut root = 1 << (n.ilog2() / 2 + 1);
+    loop {
+        let next = (root + n / root) / 2;
+        if next >= root {
+            return root;
         }
+        root = next;
     }
-    beaten_records
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn brute_force_beaten_records((time, distance): (u128, u128)) -> u128 {
+        (0..time).filter(|t| t * (time - t) > distance).count() as u128
+    }
+
     #[test]
-    fn test_get_multiple_beaten_records_test01() {
-        assert_eq!(288, get_multiple_beaten_records("input/day06_test01.txt"));
+    fn test_beaten_records_matches_brute_force() {
+        for time in 0..80 {
+            for distance in 0..=(time * time / 4 + 2) {
+                assert_eq!(
+                    brute_force_beaten_records((time, distance)),
+                    get_beaten_records((time, distance)).unwrap(),
+                    "time {} distance {}",
+                    time,
+                    distance
+                );
+            }
+        }
     }
 
     #[test]
-    fn test_get_multiple_beaten_records_test02() {
-        assert_eq!(4, get_multiple_beaten_records("input/day06_test02.txt"));
+    fn test_isqrt() {
+        for n in 0..10_000u128 {
+            let root = isqrt(n);
+            assert!(root * root <= n && (root + 1) * (root + 1) > n, "{}", n);
+        }
+        assert_eq!(u64::MAX as u128, isqrt(u128::MAX));
     }
-    
+
     #[test]
-    fn test_get_multiple_beaten_records_test03() {
-        assert_eq!(8, get_multiple_beaten_records("input/day06_test03.txt"));
+    fn test_parse_races_kerning() {
+        let lines = vec![
+            "Time:      7  15   30".to_string(),
+            "Distance:  9  40  200".to_string(),
+        ];
+        assert_eq!(
+            vec![(7, 9), (15, 40), (30, 200)],
+            parse_races(&lines, false).unwrap().races
+        );
+        assert_eq!(
+            vec![(71530, 940200)],
+            parse_races(&lines, true).unwrap().races
+        );
     }
-    
+
     #[test]
-    fn test_get_multiple_beaten_records_test04() {
-        assert_eq!(9, get_multiple_beaten_records("input/day06_test04.txt"));
+    fn test_parse_races_labels() {
+        let lines = vec!["Distance:  9".to_string(), "Time:  7".to_string()];
+        assert_eq!(
+            "<input>:1:1: expected `Time:`, found `Distance:`",
+            parse_races(&lines, false).unwrap_err().to_string()
+        );
+        let lines = vec!["Time:  7".to_string(), "Time:  9".to_string()];
+        assert_eq!(
+            "<input>:2:1: expected `Distance:`, found `Time:`",
+            parse_races(&lines, false).unwrap_err().to_string()
+        );
     }
 
     #[test]
-    fn test_get_multiple_beaten_records_part01() {
-        assert_eq!(140220, get_multiple_beaten_records("input/day06_part01.txt"));
+    fn test_beaten_records_u128() {
+        let time: u128 = 1 << 63;
+        assert_eq!(time - 1, get_beaten_records((time, 0)).unwrap());
+        assert!(get_beaten_records((u128::MAX, 0)).is_err());
     }
 
     #[test]
-    fn test_get_multiple_beaten_records_part02() {
-        assert_eq!(39570185, get_multiple_beaten_records("input/day06_part02.txt"));
+    fn test_multiple_beaten_records_overflow() {
+        let time: u128 = 1 << 63;
+        let races = Races {
+            races: vec![(time, 0), (time, 0), (time, 0)],
+        };
+        assert!(multiple_beaten_records(&races).is_err());
+        let races = Races {
+            races: vec![(time, 0), (3, 0)],
+        };
+        assert_eq!((time - 1) * 2, multiple_beaten_records(&races).unwrap());
     }
 }
@@ -1,11 +1,26 @@
 // https://adventofcode.com/2023/day/7
 
-use super::utils::get_lines;
+use super::utils::{column, parse_num, words};
+use super::{Error, Result};
 use itertools::Itertools;
 use std::cmp::Ordering;
+use tracing::trace;
 
+solution!(Day07, u64);
+
+/// Total winnings with `J` as a jack.
+pub fn part1(input: &Input) -> Result<u64> {
+    Ok(total_winnings(input, false))
+}
+
+/// Total winnings with `J` as a joker.
+pub fn part2(input: &Input) -> Result<u64> {
+    Ok(total_winnings(input, true))
+}
+
+/// A card, ranked by its discriminant.
 #[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
-enum Card {
+pub enum Card {
     Ace = 14,
     King = 13,
     Queen = 12,
@@ -22,8 +37,9 @@ enum Card {
     Joker = 1,
 }
 
+/// The kind of a hand, ranked by its discriminant.
 #[derive(Debug, PartialOrd, Ord, PartialEq, Eq)]
-enum HandType {
+pub enum HandType {
     FiveOfAKind = 7,
     FourOfAKind = 6,
     FullHouse = 5,
@@ -33,14 +49,32 @@ enum HandType {
     HighCard = 1,
 }
 
-#[derive(Debug, Eq)]
-struct Hand {
-    cards: Vec<Card>,
-    bid: u64,
+/// Five cards and their bid.
+#[derive(Debug, Clone, Eq)]
+pub struct Hand {
+    pub cards: Vec<Card>,
+    pub bid: u64,
 }
 
 impl Hand {
-    fn hand_type(&self) -> HandType {
+    /// The same hand with every jack played as a joker.
+    pub fn with_jokers(&self) -> Hand {
+        let cards = self
+            .cards
+            .iter()
+            .map(|card| match card {
+                Card::Jack => Card::Joker,
+                _ => card.clone(),
+            })
+            .collect();
+        Hand {
+            cards,
+            bid: self.bid,
+        }
+    }
+
+    /// The kind of hand, playing any jokers to its best advantage.
+    pub fn hand_type(&self) -> HandType {
         let mut sorted_cards = self.cards.to_vec();
         sorted_cards.sort();
 
@@ -78,60 +112,45 @@ impl Hand {
         if four_of_a_kind_count == 1 && joker_count == 1 {
             five_of_a_kind_count = 1;
             four_of_a_kind_count = 0;
-        }
-        else if three_of_a_kind_count == 1 && joker_count == 2 {
+        } else if three_of_a_kind_count == 1 && joker_count == 2 {
             five_of_a_kind_count = 1;
             three_of_a_kind_count = 0;
-        }
-        else if three_of_a_kind_count == 1 && joker_count == 1 {
+        } else if three_of_a_kind_count == 1 && joker_count == 1 {
             four_of_a_kind_count = 1;
             three_of_a_kind_count = 0;
-        }
-        else if pair_count == 2 && joker_count == 1 {
+        } else if pair_count == 2 && joker_count == 1 {
             three_of_a_kind_count = 1;
             pair_count = 1;
-        }
-        else if pair_count == 1 && joker_count == 3 {
+        } else if pair_count == 1 && joker_count == 3 {
             five_of_a_kind_count = 1;
             pair_count = 0;
-        }
-        else if pair_count == 1 && joker_count == 2 {
+        } else if pair_count == 1 && joker_count == 2 {
             four_of_a_kind_count = 1;
             pair_count = 0;
-        }
-        else if pair_count == 1 && joker_count == 1 {
+        } else if pair_count == 1 && joker_count == 1 {
             three_of_a_kind_count = 1;
             pair_count = 0;
-        }
-        else if joker_count == 4 || joker_count == 5 {
+        } else if joker_count == 4 || joker_count == 5 {
             five_of_a_kind_count = 1;
-        }
-        else if joker_count == 3 {
+        } else if joker_count == 3 {
             four_of_a_kind_count = 1;
-        }
-        else if joker_count == 2 {
+        } else if joker_count == 2 {
             three_of_a_kind_count = 1;
-        }
-        else if joker_count == 1 {
+        } else if joker_count == 1 {
             pair_count = 1;
         }
 
         if five_of_a_kind_count == 1 {
             hand_type = HandType::FiveOfAKind;
-        }
-        else if four_of_a_kind_count == 1 {
+        } else if four_of_a_kind_count == 1 {
             hand_type = HandType::FourOfAKind;
-        }
-        else if three_of_a_kind_count == 1 && pair_count == 1 {
+        } else if three_of_a_kind_count == 1 && pair_count == 1 {
             hand_type = HandType::FullHouse;
-        }
-        else if three_of_a_kind_count == 1 && pair_count == 0 {
+        } else if three_of_a_kind_count == 1 && pair_count == 0 {
             hand_type = HandType::ThreeOfAKind;
-        }
-        else if three_of_a_kind_count == 0 && pair_count == 2 {
+        } else if three_of_a_kind_count == 0 && pair_count == 2 {
             hand_type = HandType::TwoPair;
-        }
-        else if three_of_a_kind_count == 0 && pair_count == 1 {
+        } else if three_of_a_kind_count == 0 && pair_count == 1 {
             hand_type = HandType::OnePair;
         }
 
@@ -180,48 +199,57 @@ impl PartialEq for Hand {
     }
 }
 
+/// The hands, in input order.
 #[derive(Debug, Default)]
-struct Input {
-    hands: Vec<Hand>,
+pub struct Input {
+    pub hands: Vec<Hand>,
 }
 
-fn parse_input(input_file: &str, use_joker: bool) -> Input {
-    let lines = get_lines(input_file);
-
+fn parse_lines(lines: Vec<String>) -> Result<Input> {
     let mut input = Input::default();
 
-    for line in lines {
-        let line_parts: Vec<&str> = line.split_whitespace().collect();
-        let hand = Hand {
-            cards: parse_hand(line_parts.first().unwrap(), use_joker),
-            bid: line_parts.last().unwrap().parse().unwrap(),
+    for (line_index, line) in lines.iter().enumerate() {
+        let line_parts: Vec<(usize, &str)> = words(line).collect();
+        let hand = match line_parts[..] {
+            [hand_str, bid_str] => Hand {
+                cards: parse_hand(line_index, line, hand_str)?,
+                bid: parse_num(line_index, line, bid_str)?,
+            },
+            _ => return Err(Error::parse(line_index + 1, 1, "expected `<cards> <bid>`")),
         };
         input.hands.push(hand);
     }
 
-    input
+    Ok(input)
 }
 
-fn parse_hand(hand_str: &str, use_joker: bool) -> Vec<Card> {
+fn parse_hand(
+    line_index: usize,
+    line: &str,
+    (hand_offset, hand_str): (usize, &str),
+) -> Result<Vec<Card>> {
     let mut hand: Vec<Card> = vec![];
-    for card in hand_str.chars() {
-        hand.push(parse_card(card, use_joker));
+    for (card_offset, card) in hand_str.char_indices() {
+        match parse_card(card) {
+            Some(card) => hand.push(card),
+            None => {
+                return Err(Error::parse(
+                    line_index + 1,
+                    column(line, hand_offset + card_offset),
+                    format!("invalid card `{}`", card),
+                ))
+            }
+        }
     }
-    hand
+    Ok(hand)
 }
 
-fn parse_card(card_char: char, use_joker: bool) -> Card {
-    match card_char {
+fn parse_card(card_char: char) -> Option<Card> {
+    let card = match card_char {
         'A' => Card::Ace,
         'K' => Card::King,
         'Q' => Card::Queen,
-        'J' => {
-            if use_joker {
-                Card::Joker
-            } else {
-                Card::Jack
-            }
-        }
+        'J' => Card::Jack,
         'T' => Card::Ten,
         '9' => Card::Nine,
         '8' => Card::Eight,
@@ -231,21 +259,21 @@ fn parse_card(card_char: char, use_joker: bool) -> Card {
         '4' => Card::Four,
         '3' => Card::Three,
         '2' => Card::Two,
-        _ => panic!("Invalid card: {}", card_char),
-    }
+        _ => return None,
+    };
+    Some(card)
 }
 
-fn get_total_winnings(input_file: &str, use_joker: bool) -> u64 {
+pub(crate) fn total_winnings(input: &Input, use_joker: bool) -> u64 {
     let mut total_winnings: u64 = 0;
-    let mut input = parse_input(input_file, use_joker);
-    input.hands.sort();
-    for (rank, hand) in input.hands.iter().enumerate() {
-        /*println!(
-            "Rank: {} Hand: {:?} Hand Type: {:?}",
-            rank + 1,
-            hand,
-            hand.hand_type()
-        );*/
+    let mut hands: Vec<Hand> = if use_joker {
+        input.hands.iter().map(Hand::with_jokers).collect()
+    } else {
+        input.hands.clone()
+    };
+    hands.sort();
+    for (rank, hand) in hands.iter().enumerate() {
+        trace!(rank = rank + 1, ?hand, hand_type = ?hand.hand_type());
         total_winnings += hand.bid * (rank as u64 + 1);
     }
     total_winnings
@@ -256,37 +284,8 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_get_total_winnings_test01() {
-        assert_eq!(6440, get_total_winnings("input/day07_test01.txt", false));
-    }
-
-    #[test]
-    fn test_get_total_winnings_test02() {
-        assert_eq!(8, get_total_winnings("input/day07_test02.txt", false));
-    }
-
-    #[test]
-    fn test_get_total_winnings_test03() {
-        assert_eq!(8, get_total_winnings("input/day07_test03.txt", false));
-    }
-
-    #[test]
-    fn test_get_total_winnings() {
-        assert_eq!(251545216, get_total_winnings("input/day07.txt", false));
-    }
-
-    #[test]
-    fn test_get_total_winnings_joker_test01() {
-        assert_eq!(5905, get_total_winnings("input/day07_test01.txt", true));
-    }
-    
-    #[test]
-    fn test_get_total_winnings_joker_test04() {
-        assert_eq!(65, get_total_winnings("input/day07_test04.txt", true));
-    }
-
-    #[test]
-    fn test_get_total_winnings_joker() {
-        assert_eq!(250384185, get_total_winnings("input/day07.txt", true));
+    fn test_parse_hands_invalid_card() {
+        let err = parse_lines(vec!["32T3K 765".to_string(), "T55X5 684".to_string()]).unwrap_err();
+        assert_eq!("<input>:2:4: invalid card `X`", err.to_string());
     }
 }
@@ -1,168 +1,309 @@
 // https://adventofcode.com/2023/day/8
 
+use num::integer::{ExtendedGcd, Integer};
 use regex::Regex;
+use tracing::debug;
 
-use super::utils::get_lines;
+use super::{Error, Result};
 use std::collections::HashMap;
 
+solution!(Day08, u64);
+
+/// Steps from `AAA` to `ZZZ`.
+pub fn part1(input: &Input) -> Result<u64> {
+    num_steps(input)
+}
+
+/// Steps until every node ending in `A` is on a node ending in `Z` at once.
+pub fn part2(input: &Input) -> Result<u64> {
+    num_steps_end_with_z(input)
+}
+
+/// One left/right step of the instructions.
 #[derive(Debug, PartialEq)]
-enum Instruction {
+pub enum Instruction {
     Left,
     Right,
 }
 
+/// The instructions, the node names in input order, and each node's `(left, right)`.
 #[derive(Debug)]
-struct Input {
-    instructions: Vec<Instruction>,
-    tree_node_names: Vec<String>,
-    tree_nodes: HashMap<String, (String, String)>,
+pub struct Input {
+    pub instructions: Vec<Instruction>,
+    pub tree_node_names: Vec<String>,
+    pub tree_nodes: HashMap<String, (String, String)>,
 }
 
-fn parse_input(input_file: &str) -> Input {
+fn parse_lines(lines: Vec<String>) -> Result<Input> {
     lazy_static! {
         static ref RE_TREENODE: Regex =
             Regex::new(r"(?P<root>\w+)\s+=\s+\((?P<left>\w+),\s+(?P<right>\w+)\)").unwrap();
     }
 
-    let lines = get_lines(input_file);
-
-    let instructions = parse_instructions(lines.first().unwrap());
+    let instructions = parse_instructions(lines.first().map_or("", |line| line.as_str()))?;
 
     let mut tree_node_names: Vec<String> = vec![];
     let mut tree_nodes: HashMap<String, (String, String)> = HashMap::new();
+    let mut node_refs: Vec<(usize, usize, String)> = vec![];
 
-    for line in lines.into_iter().skip(2) {
-        let caps_tree_nodes = RE_TREENODE.captures(&line);
+    for (line_index, line) in lines.iter().enumerate().skip(2) {
+        let caps_tree_nodes = RE_TREENODE.captures(line);
         if let Some(caps_tree_nodes) = caps_tree_nodes {
             let root = caps_tree_nodes["root"].to_string();
             let left = caps_tree_nodes["left"].to_string();
             let right = caps_tree_nodes["right"].to_string();
+            for name in ["left", "right"] {
+                let node_ref = caps_tree_nodes.name(name).unwrap();
+                node_refs.push((line_index, node_ref.start(), node_ref.as_str().to_string()));
+            }
             tree_node_names.push(root.clone());
             tree_nodes.insert(root, (left, right));
+        } else if !line.is_empty() {
+            return Err(Error::parse(
+                line_index + 1,
+                1,
+                "expected `<node> = (<left>, <right>)`",
+            ));
         }
     }
 
-    tree_node_names.sort();
+    // Every node we can step to has to be defined, so traversal never looks up a missing node
+    for (line_index, start, name) in node_refs {
+        if !tree_nodes.contains_key(&name) {
+            return Err(Error::parse(
+                line_index + 1,
+                start + 1,
+                format!("unknown node `{}`", name),
+            ));
+        }
+    }
 
-    Input {
+    Ok(Input {
         instructions,
         tree_node_names,
         tree_nodes,
-    }
+    })
 }
 
-fn parse_instructions(instructions_str: &str) -> Vec<Instruction> {
+fn parse_instructions(instructions_str: &str) -> Result<Vec<Instruction>> {
+    if instructions_str.is_empty() {
+        return Err(Error::parse(1, 1, "missing instructions"));
+    }
     let mut instructions: Vec<Instruction> = vec![];
-    for instruction_str in instructions_str.chars() {
-        instructions.push(parse_instruction(instruction_str));
+    for (instruction_index, instruction_str) in instructions_str.chars().enumerate() {
+        match parse_instruction(instruction_str) {
+            Some(instruction) => instructions.push(instruction),
+            None => {
+                return Err(Error::parse(
+                    1,
+                    instruction_index + 1,
+                    format!("invalid instruction `{}`", instruction_str),
+                ))
+            }
+        }
     }
-    instructions
+    Ok(instructions)
 }
 
-fn parse_instruction(instruction_char: char) -> Instruction {
+fn parse_instruction(instruction_char: char) -> Option<Instruction> {
     match instruction_char {
-        'L' => Instruction::Left,
-        'R' => Instruction::Right,
-        _ => panic!("Invalid instruction: {}", instruction_char),
-    }
-}
-
-fn get_num_steps(input_file: &str) -> u64 {
-    let mut num_steps: u64 = 0;
-    let input = parse_input(input_file);
-    let mut current_node = Some(input.tree_node_names.first().unwrap().clone());
-    for instruction in input.instructions.iter().cycle() {
-        current_node = traverse_tree(
-            &input.tree_nodes,
-            current_node.unwrap(),
-            input.tree_node_names.last().unwrap().clone(),
-            instruction,
-        );
-        num_steps += 1;
-        if current_node.is_none() {
-            break;
-        }
+        'L' => Some(Instruction::Left),
+        'R' => Some(Instruction::Right),
+        _ => None,
     }
+}
 
-    num_steps
+/// The states of a walk through the network from a start node, as `(steps taken, node)`, until the
+/// walk comes back to a node at the same point in the instructions and would repeat itself.
+struct Walk<'a> {
+    input: &'a Input,
+    node: &'a str,
+    num_steps: u64,
+    seen: HashMap<(&'a str, usize), u64>,
+    /// Once the walk has ended, the step it would repeat from
+    cycle_start: Option<u64>,
 }
 
-fn get_num_steps_end_with_z(input_file: &str) -> u64 {
-    let mut num_steps_vec: Vec<u64> = vec![];
+impl<'a> Walk<'a> {
+    fn new(input: &'a Input, start_node: &str) -> Result<Walk<'a>> {
+        let (node, _) = input.tree_nodes.get_key_value(start_node).ok_or_else(|| {
+            Error::NoSolution(format!("the network has no node `{}`", start_node))
+        })?;
+        Ok(Walk {
+            input,
+            node,
+            num_steps: 0,
+            seen: HashMap::new(),
+            cycle_start: None,
+        })
+    }
+}
 
-    let input = parse_input(input_file);
-    let start_nodes: Vec<String> = input
-        .tree_node_names
-        .into_iter()
-        .filter(|e| e.ends_with('A'))
-        .collect();
-    for start_node in start_nodes {
-        let mut num_steps: u64 = 0;
-        let mut current_node = Some(start_node);
-        for instruction in input.instructions.iter().cycle() {
-            current_node =
-                traverse_tree_ends_with_z(&input.tree_nodes, current_node.unwrap(), instruction);
-            num_steps += 1;
-            if current_node.is_none() {
-                num_steps_vec.push(num_steps);
-                break;
-            }
+impl<'a> Iterator for Walk<'a> {
+    type Item = (u64, &'a str);
+
+    fn next(&mut self) -> Option<(u64, &'a str)> {
+        if self.cycle_start.is_some() {
+            return None;
+        }
+        let instruction_index = self.num_steps as usize % self.input.instructions.len();
+        if let Some(&cycle_start) = self.seen.get(&(self.node, instruction_index)) {
+            self.cycle_start = Some(cycle_start);
+            return None;
         }
+        self.seen
+            .insert((self.node, instruction_index), self.num_steps);
+
+        let state = (self.num_steps, self.node);
+        self.node = step(
+            self.input,
+            self.node,
+            &self.input.instructions[instruction_index],
+        );
+        self.num_steps += 1;
+        Some(state)
     }
+}
 
-    let lcm = num_steps_vec.into_iter().fold(1, num::integer::lcm);
+/// Steps from `start_node` to the first node that satisfies `is_goal`.
+pub fn steps_until(input: &Input, start_node: &str, is_goal: impl Fn(&str) -> bool) -> Result<u64> {
+    Walk::new(input, start_node)?
+        .find(|&(_, node)| is_goal(node))
+        .map(|(num_steps, _)| num_steps)
+        .ok_or_else(|| Error::NoSolution(format!("the goal is unreachable from `{}`", start_node)))
+}
 
-    println!("{:?}", lcm);
+fn num_steps(input: &Input) -> Result<u64> {
+    steps_until(input, "AAA", |node| node == "ZZZ")
+}
 
-    lcm
+/// Where a ghost's walk from its start node lands on `..Z` nodes.
+///
+/// The walk's state is its node and its position in the instructions, so it repeats after at most
+/// `nodes * instructions` steps: the first `tail` steps never recur, and from then on it goes round
+/// the same `cycle` steps forever.
+#[derive(Debug, PartialEq)]
+struct GhostCycle {
+    tail: u64,
+    cycle: u64,
+    /// Steps before the cycle that land on a `..Z` node
+    tail_z_steps: Vec<u64>,
+    /// Steps into the cycle, from `tail`, that land on a `..Z` node
+    cycle_z_offsets: Vec<u64>,
 }
 
-fn traverse_tree(
-    tree_nodes: &HashMap<String, (String, String)>,
-    current_node: String,
-    leaf_node: String,
-    instruction: &Instruction,
-) -> Option<String> {
-    match tree_nodes.get(&current_node) {
-        Some((left, right)) => {
-            if *instruction == Instruction::Left && *current_node != *left && *left != leaf_node {
-                return Some(left.clone());
-            } else if *instruction == Instruction::Right
-                && *current_node != *right
-                && *right != leaf_node
-            {
-                return Some(right.clone());
-            }
+impl GhostCycle {
+    fn is_z_at(&self, step: u64) -> bool {
+        if step < self.tail {
+            self.tail_z_steps.contains(&step)
+        } else {
+            self.cycle_z_offsets
+                .contains(&((step - self.tail) % self.cycle))
         }
-        None => panic!("Invalid current_node"),
-    }
-    None
-}
-
-fn traverse_tree_ends_with_z(
-    tree_nodes: &HashMap<String, (String, String)>,
-    current_node: String,
-    instruction: &Instruction,
-) -> Option<String> {
-    match tree_nodes.get(&current_node) {
-        Some((left, right)) => {
-            if *instruction == Instruction::Left
-                && *current_node != *left
-                && !left.ends_with('Z')
-                && !current_node.ends_with('Z')
-            {
-                return Some(left.clone());
-            } else if *instruction == Instruction::Right
-                && *current_node != *right
-                && !right.ends_with('Z')
-                && !current_node.ends_with('Z')
-            {
-                return Some(right.clone());
+    }
+}
+
+fn step<'a>(input: &'a Input, node: &str, instruction: &Instruction) -> &'a str {
+    let (left, right) = &input.tree_nodes[node];
+    match instruction {
+        Instruction::Left => left,
+        Instruction::Right => right,
+    }
+}
+
+fn ghost_cycle(input: &Input, start_node: &str) -> Result<GhostCycle> {
+    let mut walk = Walk::new(input, start_node)?;
+    let z_steps: Vec<u64> = walk
+        .by_ref()
+        .filter(|(_, node)| node.ends_with('Z'))
+        .map(|(num_steps, _)| num_steps)
+        .collect();
+    // The walk only ends once it's found where it repeats from
+    let tail = walk.cycle_start.unwrap_or(walk.num_steps);
+    let (tail_z_steps, cycle_z_steps): (Vec<u64>, Vec<u64>) =
+        z_steps.into_iter().partition(|&z_step| z_step < tail);
+    Ok(GhostCycle {
+        tail,
+        cycle: walk.num_steps - tail,
+        tail_z_steps,
+        cycle_z_offsets: cycle_z_steps.iter().map(|z_step| z_step - tail).collect(),
+    })
+}
+
+fn num_steps_end_with_z(input: &Input) -> Result<u64> {
+    let ghost_cycles: Vec<GhostCycle> = input
+        .tree_node_names
+        .iter()
+        .filter(|e| e.ends_with('A'))
+        .map(|start_node| ghost_cycle(input, start_node))
+        .collect::<Result<_>>()?;
+    debug!(?ghost_cycles);
+
+    first_common_z_step(&ghost_cycles).ok_or_else(|| {
+        Error::NoSolution("the ghosts are never all on `..Z` nodes at once".to_string())
+    })
+}
+
+fn first_common_z_step(ghost_cycles: &[GhostCycle]) -> Option<u64> {
+    let max_tail = ghost_cycles.iter().map(|ghost| ghost.tail).max()?;
+
+    // Before every ghost is in its cycle, the ghost with the longest tail can only be on a `..Z`
+    // node at one of its tail steps
+    let longest_tail = ghost_cycles.iter().find(|ghost| ghost.tail == max_tail)?;
+    let tail_step = longest_tail
+        .tail_z_steps
+        .iter()
+        .copied()
+        .find(|&z_step| ghost_cycles.iter().all(|ghost| ghost.is_z_at(z_step)));
+    if tail_step.is_some() {
+        return tail_step;
+    }
+
+    // After that, each ghost is on a `..Z` node at `tail + offset` modulo its cycle, for one of its
+    // offsets, so solve every combination of congruences
+    let mut congruences: Vec<(u128, u128)> = vec![(0, 1)];
+    for ghost in ghost_cycles {
+        congruences = congruences
+            .iter()
+            .flat_map(|&congruence| {
+                ghost.cycle_z_offsets.iter().filter_map(move |offset| {
+                    let cycle = ghost.cycle as u128;
+                    let residue = (ghost.tail + offset) as u128 % cycle;
+                    crt(congruence, (residue, cycle))
+                })
+            })
+            .collect();
+        congruences.sort();
+        congruences.dedup();
+    }
+
+    congruences
+        .into_iter()
+        .map(|(residue, modulus)| {
+            let max_tail = max_tail as u128;
+            if residue >= max_tail {
+                residue
+            } else {
+                residue + (max_tail - residue).div_ceil(modulus) * modulus
             }
-        }
-        None => panic!("Invalid current_node"),
+        })
+        .min()
+        .and_then(|z_step| u64::try_from(z_step).ok())
+}
+
+/// The `(residue, modulus)` of the numbers that satisfy both congruences, if there are any. The
+/// moduli don't have to be coprime.
+fn crt((r1, m1): (u128, u128), (r2, m2): (u128, u128)) -> Option<(u128, u128)> {
+    let (r1, m1, r2, m2) = (r1 as i128, m1 as i128, r2 as i128, m2 as i128);
+    let ExtendedGcd { gcd, x, .. } = m1.extended_gcd(&m2);
+    if (r2 - r1) % gcd != 0 {
+        return None;
     }
-    None
+    let modulus = m1 / gcd * m2;
+    // m1 * x = gcd (mod m2), so stepping r1 by m1 * x * (r2 - r1) / gcd lands on r2 (mod m2)
+    let k = ((r2 - r1) / gcd % (m2 / gcd)) * (x % (m2 / gcd)) % (m2 / gcd);
+    let residue = (r1 + m1 * k).rem_euclid(modulus);
+    Some((residue as u128, modulus as u128))
 }
 
 #[cfg(test)]
@@ -170,27 +311,129 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_get_num_steps_test01() {
-        assert_eq!(2, get_num_steps("input/day08_test01.txt"));
+    fn test_parse_network_unknown_node() {
+        let lines = vec!["LR", "", "AAA = (BBB, CCC)", "BBB = (AAA, AAA)"];
+        let err = parse_lines(lines.into_iter().map(String::from).collect()).unwrap_err();
+        assert_eq!("<input>:3:13: unknown node `CCC`", err.to_string());
+    }
+
+    fn network(lines: &[&str]) -> Input {
+        parse_lines(lines.iter().map(|line| line.to_string()).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_ghost_cycle() {
+        let input = network(&[
+            "L",
+            "",
+            "11A = (11B, XXX)",
+            "11B = (11C, XXX)",
+            "11C = (11Z, XXX)",
+            "11Z = (11D, XXX)",
+            "11D = (11E, XXX)",
+            "11E = (11B, XXX)",
+            "XXX = (XXX, XXX)",
+        ]);
+        assert_eq!(
+            GhostCycle {
+                tail: 1,
+                cycle: 5,
+                tail_z_steps: vec![],
+                cycle_z_offsets: vec![2],
+            },
+            ghost_cycle(&input, "11A").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_num_steps_end_with_z_offset_cycle() {
+        // 11A is on 11Z at steps 3, 8, 13, ... and 22A on 22Z at 2, 4, 6, ..., so the lcm of the
+        // first hits (6) is wrong
+        let input = network(&[
+            "L",
+            "",
+            "11A = (11B, XXX)",
+            "11B = (11C, XXX)",
+            "11C = (11Z, XXX)",
+            "11Z = (11D, XXX)",
+            "11D = (11E, XXX)",
+            "11E = (11B, XXX)",
+            "22A = (22B, XXX)",
+            "22B = (22Z, XXX)",
+            "22Z = (22B, XXX)",
+            "XXX = (XXX, XXX)",
+        ]);
+        assert_eq!(8, num_steps_end_with_z(&input).unwrap());
     }
 
     #[test]
-    fn test_get_num_steps_test02() {
-        assert_eq!(6, get_num_steps("input/day08_test02.txt"));
+    fn test_num_steps_end_with_z_in_tail() {
+        // 11A passes 11Z once on its way into a cycle without one, as 22A reaches 22Z
+        let input = network(&[
+            "L",
+            "",
+            "11A = (11Z, XXX)",
+            "11Z = (11B, XXX)",
+            "11B = (11B, XXX)",
+            "22A = (22Z, XXX)",
+            "22Z = (22Z, XXX)",
+            "XXX = (XXX, XXX)",
+        ]);
+        assert_eq!(1, num_steps_end_with_z(&input).unwrap());
     }
 
     #[test]
-    fn test_get_num_steps() {
-        assert_eq!(21883, get_num_steps("input/day08.txt"));
+    fn test_num_steps_end_with_z_no_solution() {
+        // 11A is on 11Z at even steps and 22A on 22Z at odd ones
+        let input = network(&[
+            "L",
+            "",
+            "11A = (11B, XXX)",
+            "11B = (11Z, XXX)",
+            "11Z = (11B, XXX)",
+            "22A = (22Z, XXX)",
+            "22Z = (22B, XXX)",
+            "22B = (22Z, XXX)",
+            "XXX = (XXX, XXX)",
+        ]);
+        assert!(matches!(
+            num_steps_end_with_z(&input),
+            Err(Error::NoSolution(_))
+        ));
     }
 
     #[test]
-    fn test_get_num_steps_end_with_z_test03() {
-        assert_eq!(6, get_num_steps_end_with_z("input/day08_test03.txt"));
+    fn test_crt() {
+        assert_eq!(Some((8, 10)), crt((3, 5), (0, 2)));
+        assert_eq!(Some((10, 12)), crt((4, 6), (2, 4)));
+        assert_eq!(None, crt((0, 2), (1, 4)));
     }
 
     #[test]
-    fn test_get_num_steps_end_with_z() {
-        assert_eq!(12833235391111, get_num_steps_end_with_z("input/day08.txt"));
+    fn test_steps_until() {
+        // The goal comes first in the file, so it can't be found by sorting names either
+        let input = network(&[
+            "L",
+            "",
+            "ZZZ = (ZZZ, ZZZ)",
+            "BBB = (ZZZ, AAA)",
+            "AAA = (BBB, AAA)",
+            "CCC = (CCC, AAA)",
+        ]);
+        assert_eq!(2, num_steps(&input).unwrap());
+        assert_eq!(1, steps_until(&input, "AAA", |node| node == "BBB").unwrap());
+        assert_eq!(0, steps_until(&input, "AAA", |node| node == "AAA").unwrap());
+        assert_eq!(
+            "no solution: the goal is unreachable from `AAA`",
+            steps_until(&input, "AAA", |node| node == "CCC")
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "no solution: the network has no node `DDD`",
+            steps_until(&input, "DDD", |node| node == "ZZZ")
+                .unwrap_err()
+                .to_string()
+        );
     }
 }
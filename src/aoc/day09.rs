@@ -1,37 +1,52 @@
 // https://adventofcode.com/2023/day/9
 
-use super::utils::get_lines;
+use tracing::trace;
 
-#[derive(Debug)]
-struct Input {
-    reports: Vec<Vec<i64>>,
+use super::utils::{parse_num, words};
+use super::{Error, Result};
+
+solution!(Day09, i64);
+
+/// Sum of the next value of every history.
+pub fn part1(input: &Input) -> Result<i64> {
+    Ok(sum_extrapolated_values(input))
+}
+
+/// Sum of the previous value of every history.
+pub fn part2(input: &Input) -> Result<i64> {
+    Ok(sum_back_extrapolated_values(input))
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
+/// The value histories.
+#[derive(Debug)]
+pub struct Input {
+    pub reports: Vec<Vec<i64>>,
+}
 
-    Input {
-        reports: parse_reports(lines),
-    }
+fn parse_lines(lines: Vec<String>) -> Result<Input> {
+    Ok(Input {
+        reports: parse_reports(lines)?,
+    })
 }
 
-fn parse_reports(lines: Vec<String>) -> Vec<Vec<i64>> {
+fn parse_reports(lines: Vec<String>) -> Result<Vec<Vec<i64>>> {
     let mut reports = vec![];
-    for line in lines {
-        let report: Vec<i64> = line
-            .split_ascii_whitespace()
-            .map(|x| x.parse().unwrap())
-            .collect();
+    for (line_index, line) in lines.iter().enumerate() {
+        let report: Vec<i64> = words(line)
+            .map(|x| parse_num(line_index, line, x))
+            .collect::<Result<_>>()?;
+        if report.is_empty() {
+            return Err(Error::parse(line_index + 1, 1, "empty report"));
+        }
         reports.push(report)
     }
-    reports
+    Ok(reports)
 }
 
-fn get_sum_extrapolated_values(input_file: &str) -> i64 {
-    let input = parse_input(input_file);
+fn sum_extrapolated_values(input: &Input) -> i64 {
     let mut extrapolated_values: Vec<i64> = vec![];
-    for report in input.reports {
-        extrapolated_values.push(extrapolate_next_value(&report));
+    for report in &input.reports {
+        extrapolated_values.push(extrapolate_next_value(report));
     }
     extrapolated_values.iter().sum()
 }
@@ -44,84 +59,23 @@ fn extrapolate_next_value(report: &Vec<i64>) -> i64 {
         .collect::<Vec<_>>();
 
     let next_val = if next_report.iter().all(|x| *x == 0) {
-        println!("{:?}", next_report);
+        trace!(?next_report);
         *report.last().unwrap()
     } else {
         let val = extrapolate_next_value(&next_report);
         *report.last().unwrap() + val
     };
 
-    println!("{:?} {}", report, next_val);
+    trace!(?report, next_val);
 
     next_val
 }
 
-fn get_sum_back_extrapolated_values(input_file: &str) -> i64 {
-    let input = parse_input(input_file);
+fn sum_back_extrapolated_values(input: &Input) -> i64 {
     let mut extrapolated_values: Vec<i64> = vec![];
-    for report in input.reports {
-        let reversed_report: Vec<i64> = report.into_iter().rev().collect();
+    for report in &input.reports {
+        let reversed_report: Vec<i64> = report.iter().rev().copied().collect();
         extrapolated_values.push(extrapolate_next_value(&reversed_report));
     }
     extrapolated_values.iter().sum()
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_get_sum_extrapolated_values_test01() {
-        assert_eq!(18, get_sum_extrapolated_values("input/day09_test01.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_extrapolated_values_test02() {
-        assert_eq!(28, get_sum_extrapolated_values("input/day09_test02.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_extrapolated_values_test03() {
-        assert_eq!(68, get_sum_extrapolated_values("input/day09_test03.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_extrapolated_values_test04() {
-        assert_eq!(114, get_sum_extrapolated_values("input/day09_test04.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_extrapolated_values_test05() {
-        assert_eq!(-106, get_sum_extrapolated_values("input/day09_test05.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_extrapolated_values_test06() {
-        assert_eq!(
-            0,
-            get_sum_extrapolated_values("input/day09_test06.txt")
-        );
-    }
-
-    #[test]
-    fn test_get_sum_extrapolated_values() {
-        assert_eq!(1708206096, get_sum_extrapolated_values("input/day09.txt"));
-    }
-
-    #[test]
-    fn test_get_sum_back_extrapolated_values_test01() {
-        assert_eq!(
-            5,
-            get_sum_back_extrapolated_values("input/day09_test03.txt")
-        );
-    }
-
-    #[test]
-    fn test_get_sum_back_extrapolated_values() {
-        assert_eq!(
-            1050,
-            get_sum_back_extrapolated_values("input/day09.txt")
-        );
-    }
-
-}
@@ -1,15 +1,32 @@
 // https://adventofcode.com/2023/day/10
 
+use std::fmt;
+
 use enum_iterator::Sequence;
 use grid::Grid;
-use indextree::{Arena, NodeEdge, NodeId};
 use itertools::Itertools;
+use tracing::{debug, trace};
+
+use super::export::{CellStyle, Rgb};
+use super::utils::grid::{parse_grid, GridExt, Offset, Pos, EAST, NORTH, SOUTH, WEST};
+use super::{Error, Result};
+
+solution!(Day10, usize);
+
+/// Steps along the loop to the tile farthest from the start.
+pub fn part1(input: &Input) -> Result<usize> {
+    farthest_steps(input)
+}
 
-use super::utils::get_lines;
+/// Tiles enclosed by the loop.
+pub fn part2(input: &Input) -> Result<usize> {
+    enclosed_by_loop(input)
+}
 
+/// A tile of the pipe map.
 #[derive(Debug, Default, PartialEq, Eq, Copy, Clone, Sequence)]
 #[repr(u8)]
-enum Pipe {
+pub enum Pipe {
     #[default]
     Ground = b'.',
     VerticalNS = b'|',
@@ -31,10 +48,24 @@ enum Direction {
     W,
 }
 
+/// The directions out of a tile, in reading order.
+const DIRECTIONS: [Direction; 4] = [Direction::N, Direction::W, Direction::E, Direction::S];
+
+impl Direction {
+    fn offset(self) -> Offset {
+        match self {
+            Direction::N => NORTH,
+            Direction::S => SOUTH,
+            Direction::E => EAST,
+            Direction::W => WEST,
+        }
+    }
+}
+
 impl TryFrom<u8> for Pipe {
-    type Error = ();
+    type Error = u8;
 
-    fn try_from(v: u8) -> Result<Self, Self::Error> {
+    fn try_from(v: u8) -> std::result::Result<Self, Self::Error> {
         match v {
             x if x == Pipe::Ground as u8 => Ok(Pipe::Ground),
             x if x == Pipe::VerticalNS as u8 => Ok(Pipe::VerticalNS),
@@ -46,174 +77,357 @@ impl TryFrom<u8> for Pipe {
             x if x == Pipe::StartPos as u8 => Ok(Pipe::StartPos),
             x if x == Pipe::Inside as u8 => Ok(Pipe::Inside),
             x if x == Pipe::Outside as u8 => Ok(Pipe::Outside),
-            _ => Err(()),
+            _ => Err(v),
         }
     }
 }
 
+/// The tiles of the pipe map, and the start tile with the pipe hidden under it.
 #[derive(Debug)]
-struct Input {
-    tiles: Grid<Pipe>,
+pub struct Input {
+    pub tiles: Grid<Pipe>,
+    pub start: Pos,
+    pub start_pipe: Pipe,
 }
 
-fn parse_input(input_file: &str) -> Input {
-    let lines = get_lines(input_file);
-
+fn parse_lines(lines: Vec<String>) -> Result<Input> {
     let mut iter = lines.split(|e| e.is_empty());
 
-    Input {
-        tiles: parse_tiles(iter.next().unwrap().to_owned()),
+    match check_tiles(iter.next().unwrap_or_default()) {
+        Ok(input) => Ok(input),
+        Err(problems) => Err(problems[0].to_error()),
     }
 }
 
-fn parse_tiles(tiles_lines: Vec<String>) -> Grid<Pipe> {
-    let mut tiles = Grid::new(0, 0);
-    for tiles_line in tiles_lines.into_iter() {
-        let mut tiles_entries: Vec<Pipe> = Vec::new();
-        for tiles_entry in tiles_line.chars() {
-            match Pipe::try_from(tiles_entry as u8) {
-                Ok(pipe) => tiles_entries.push(pipe),
-                Err(_) => panic!("Invalid pipe"),
+fn parse_tiles(tiles_lines: Vec<String>) -> Result<Grid<Pipe>> {
+    parse_grid(
+        &tiles_lines,
+        |line_index, col, tiles_entry| match u8::try_from(tiles_entry).map(Pipe::try_from) {
+            Ok(Ok(pipe)) => Ok(pipe),
+            _ => Err(Error::parse(
+                line_index + 1,
+                col + 1,
+                format!("invalid pipe `{}`", tiles_entry),
+            )),
+        },
+    )
+}
+
+/// Something wrong with a pipe map, found by [`validate`]. Positions are `(row, col)`, from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// A row with a different number of tiles to the first row
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A character that isn't a pipe, ground or the start tile
+    InvalidTile { pos: Pos, tile: char },
+    /// There's no start tile `S`
+    NoStart,
+    /// There's more than one start tile `S`
+    MultipleStarts(Vec<Pos>),
+    /// More than two pipes connect to the start tile, and `loops` pairs of them close a loop,
+    /// rather than exactly one
+    StartOverconnected {
+        pos: Pos,
+        neighbours: Vec<Pos>,
+        loops: usize,
+    },
+    /// The pipes from the start tile stop at `end` without getting back to it
+    UnclosedLoop { end: Pos },
+}
+
+/// A position as a 1-based `line:column`, like parse errors.
+fn line_col((row, col): Pos) -> String {
+    format!("{}:{}", row + 1, col + 1)
+}
+
+impl Problem {
+    /// Where the problem is, if it's anywhere in particular.
+    pub fn pos(&self) -> Option<Pos> {
+        match self {
+            Problem::RaggedRow {
+                row,
+                expected,
+                found,
+            } => Some((*row, *expected.min(found))),
+            Problem::InvalidTile { pos, .. }
+            | Problem::StartOverconnected { pos, .. }
+            | Problem::UnclosedLoop { end: pos } => Some(*pos),
+            Problem::NoStart => None,
+            // The first start tile is as good as any, so blame the second
+            Problem::MultipleStarts(positions) => positions.get(1).copied(),
+        }
+    }
+
+    fn message(&self) -> String {
+        let line_cols = |positions: &[Pos]| positions.iter().map(|&pos| line_col(pos)).join(", ");
+        match self {
+            Problem::RaggedRow {
+                expected, found, ..
+            } => format!("expected {} columns, found {}", expected, found),
+            Problem::InvalidTile { tile, .. } => format!("invalid pipe `{}`", tile),
+            Problem::NoStart => "the tiles have no start tile `S`".to_string(),
+            Problem::MultipleStarts(positions) => format!(
+                "{} start tiles `S`, at {}",
+                positions.len(),
+                line_cols(positions)
+            ),
+            Problem::StartOverconnected {
+                neighbours, loops, ..
+            } => format!(
+                "{} pipes connect to the start tile, at {}, and {} pairs of them close a loop",
+                neighbours.len(),
+                line_cols(neighbours),
+                loops
+            ),
+            Problem::UnclosedLoop { .. } => {
+                "the loop from the start tile stops here, without getting back to it".to_string()
             }
         }
-        tiles.push_row(tiles_entries)
     }
-    tiles
-}
 
-#[derive(Debug, Copy, Clone)]
-struct Entry {
-    pipe: Pipe,
-    pos: (usize, usize),
-    direction: Option<Direction>,
+    /// The problem as a parse error at its position, or at the start of the input if it has none.
+    fn to_error(&self) -> Error {
+        let (row, col) = self.pos().unwrap_or((0, 0));
+        Error::parse(row + 1, col + 1, self.message())
+    }
 }
 
-fn get_farthest_steps(input_file: &str) -> usize {
-    let input = parse_input(input_file);
-
-    let mut start_pos: Option<(usize, usize)> = None;
-    for ((row, col), pipe) in input.tiles.indexed_iter() {
-        if *pipe == Pipe::StartPos {
-            start_pos = Some((row, col));
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.pos() {
+            Some(pos) => write!(f, "{}: {}", line_col(pos), self.message()),
+            None => write!(f, "{}", self.message()),
         }
     }
+}
 
-    if let Some((row, col)) = start_pos {
-        let mut arena: Arena<Entry> = Arena::new();
-        let root_node = arena.new_node(Entry {
-            pipe: Pipe::StartPos,
-            pos: (row, col),
-            direction: None,
-        });
+/// What [`validate`] found out about a pipe map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Everything wrong with the map, in the order it was found
+    pub problems: Vec<Problem>,
+    /// The pipe under the start tile, if the map has no problems
+    pub start_pipe: Option<Pipe>,
+}
 
-        build_tree(&input.tiles, &mut arena, None, root_node);
+impl Report {
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
 
-        let traverser = root_node.traverse(&arena);
-        let mut node_ids: Vec<NodeId> = vec![];
-        for ev in traverser {
-            match ev {
-                indextree::NodeEdge::Start(id) => node_ids.push(id),
-                _ => break,
-            };
+/// Checks the raw puzzle input for anything that stops the loop being traced, and works out the
+/// pipe under the start tile. [`parse`] runs the same checks, failing on the first problem.
+///
+/// Problems with the rows and tiles are all reported together, but the start tile and the loop are
+/// only checked once the map is a grid with a single start tile.
+pub fn validate(input: &str) -> Report {
+    let lines = input
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .map(String::from)
+        .collect_vec();
+    match check_tiles(&lines) {
+        Ok(input) => Report {
+            problems: vec![],
+            start_pipe: Some(input.start_pipe),
+        },
+        Err(problems) => Report {
+            problems,
+            start_pipe: None,
+        },
+    }
+}
+
+/// Parses the lines of a pipe map, or finds the problems with it that [`validate`] reports.
+fn check_tiles(lines: &[String]) -> std::result::Result<Input, Vec<Problem>> {
+    let expected = lines.first().map_or(0, |line| line.chars().count());
+    let mut problems = vec![];
+    let mut starts = vec![];
+    for (row, line) in lines.iter().enumerate() {
+        let found = line.chars().count();
+        if found != expected {
+            problems.push(Problem::RaggedRow {
+                row,
+                expected,
+                found,
+            });
         }
-        let res = node_ids.len() / 2;
-        println!("{}", res);
-        res
-    } else {
-        panic!("Invalid start node");
+        for (col, tile) in line.chars().enumerate() {
+            match u8::try_from(tile).map(Pipe::try_from) {
+                Ok(Ok(Pipe::StartPos)) => starts.push((row, col)),
+                Ok(Ok(_)) => (),
+                _ => problems.push(Problem::InvalidTile {
+                    pos: (row, col),
+                    tile,
+                }),
+            }
+        }
+    }
+    match starts[..] {
+        [] => problems.push(Problem::NoStart),
+        [_] => (),
+        _ => problems.push(Problem::MultipleStarts(starts.clone())),
+    }
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+    // The rows are all the same, non-zero, length and every tile is valid, so the grid parses
+    let tiles = parse_tiles(lines.to_vec()).expect("checked rows parse");
+
+    let start = starts[0];
+    let (directions, neighbours): (Vec<Direction>, Vec<Pos>) = DIRECTIONS
+        .into_iter()
+        .filter_map(|direction| {
+            let pos = tiles.offset(start, direction.offset())?;
+            is_pipe_connected(Pipe::StartPos, tiles[pos], direction).then_some((direction, pos))
+        })
+        .unzip();
+    if neighbours.len() <= 2 {
+        // With fewer than two pipes connecting to it, the start tile can't be on a loop, but
+        // walking from it anyway finds where the pipes stop
+        return match close_loop(&tiles, start, &directions) {
+            Ok(start_pipe) => Ok(Input {
+                tiles,
+                start,
+                start_pipe,
+            }),
+            Err(end) => Err(vec![Problem::UnclosedLoop { end }]),
+        };
+    }
+
+    // The loop goes through two of the pipes connecting to the start tile, so the map is only
+    // ambiguous when no pair of them, or more than one, closes a loop
+    let start_pipes = directions
+        .into_iter()
+        .tuple_combinations()
+        .filter_map(|(first, second)| close_loop(&tiles, start, &[first, second]).ok())
+        .collect_vec();
+    match start_pipes[..] {
+        [start_pipe] => Ok(Input {
+            tiles,
+            start,
+            start_pipe,
+        }),
+        _ => Err(vec![Problem::StartOverconnected {
+            pos: start,
+            neighbours,
+            loops: start_pipes.len(),
+        }]),
     }
 }
 
-fn build_tree(
+/// The pipe under the start tile when it opens in `directions`, if the loop walked from it gets
+/// back to it, or else the end of the walk.
+fn close_loop(
     tiles: &Grid<Pipe>,
-    arena: &mut Arena<Entry>,
-    maybe_prev_index: Option<NodeId>,
-    current_index: NodeId,
-) {
-    let maybe_node = arena.get_mut(current_index);
-    if let Some(current_node) = maybe_node {
-        let current_path = current_node.get().pipe;
-        let current_row: i64 = current_node.get().pos.0 as i64;
-        let current_col: i64 = current_node.get().pos.1 as i64;
-        // Loop through all the potential new nodes to check next direction
-        for test_row in (current_row - 1)..(current_row + 2) {
-            for test_col in (current_col - 1)..(current_col + 2) {
-                let maybe_direction = get_direction(
-                    (current_row as i32, current_col as i32),
-                    (test_row as i32, test_col as i32),
-                );
-                if let Some(next_direction) = maybe_direction {
-                    // Don't consider our current node, and only consider NSEW
-                    if !(current_row == test_row && current_col == test_col)
-                        && (next_direction == Direction::N
-                            || next_direction == Direction::S
-                            || next_direction == Direction::E
-                            || next_direction == Direction::W)
-                    {
-                        if let Some(next_pipe) = tiles.get(test_row, test_col) {
-                            if let Some(prev_index) = maybe_prev_index {
-                                let maybe_prev_node = arena.get(prev_index);
-                                if let Some(prev_node) = maybe_prev_node {
-                                    if prev_node.get().pos != (test_row as usize, test_col as usize)
-                                    {
-                                        if is_pipe_connected(
-                                            current_path,
-                                            *next_pipe,
-                                            next_direction,
-                                        ) {
-                                            let new_node = arena.new_node(Entry {
-                                                pipe: *next_pipe,
-                                                pos: (test_row as usize, test_col as usize),
-                                                direction: Some(next_direction),
-                                            });
-                                            current_index.append(new_node, arena);
-                                            build_tree(tiles, arena, Some(current_index), new_node)
-                                        }
-                                    }
-                                }
-                            } else {
-                                if is_pipe_connected(current_path, *next_pipe, next_direction) {
-                                    let new_node = arena.new_node(Entry {
-                                        pipe: *next_pipe,
-                                        pos: (test_row as usize, test_col as usize),
-                                        direction: Some(next_direction),
-                                    });
-                                    current_index.append(new_node, arena);
-                                    build_tree(tiles, arena, Some(current_index), new_node)
-                                }
-                            }
-                        }
-                    }
-                }
+    start: Pos,
+    directions: &[Direction],
+) -> std::result::Result<Pipe, Pos> {
+    let start_pipe = start_pipe(directions);
+    let loop_tiles = trace_loop(tiles, start, start_pipe.unwrap_or(Pipe::StartPos));
+    let end = loop_tiles[loop_tiles.len() - 1];
+    // Walking never steps back onto the start tile, so a closed loop ends on a pipe opening into it,
+    // from the other side of the start tile to where the walk set off
+    let closed = loop_tiles.len() > 2
+        && directions
+            .iter()
+            .any(|direction| tiles.offset(start, direction.offset()) == Some(end.pos))
+        && DIRECTIONS.into_iter().any(|direction| {
+            tiles.offset(end.pos, direction.offset()) == Some(start) && opens(end.pipe, direction)
+        });
+    match start_pipe {
+        Some(start_pipe) if closed => Ok(start_pipe),
+        _ => Err(end.pos),
+    }
+}
+
+/// The pipe opening in exactly `directions`, which are in [`DIRECTIONS`] order.
+fn start_pipe(directions: &[Direction]) -> Option<Pipe> {
+    match directions {
+        [Direction::N, Direction::W] => Some(Pipe::NW90DegJSym),
+        [Direction::N, Direction::E] => Some(Pipe::NE90DegLSym),
+        [Direction::W, Direction::S] => Some(Pipe::SW90Deg7Sym),
+        [Direction::E, Direction::S] => Some(Pipe::SE90DegFSym),
+        [Direction::N, Direction::S] => Some(Pipe::VerticalNS),
+        [Direction::W, Direction::E] => Some(Pipe::HorizontalEW),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Entry {
+    pipe: Pipe,
+    pos: (usize, usize),
+}
+
+fn farthest_steps(input: &Input) -> Result<usize> {
+    let loop_tiles = trace_loop(&input.tiles, input.start, input.start_pipe);
+    let res = loop_tiles.len() / 2;
+    debug!(farthest_steps = res);
+    Ok(res)
+}
+
+/// The tiles of the loop through `start_pos`, in the order they're walked, starting with the start
+/// tile as `start_pipe`.
+fn trace_loop(tiles: &Grid<Pipe>, start_pos: Pos, start_pipe: Pipe) -> Vec<Entry> {
+    let mut loop_tiles = vec![Entry {
+        pipe: start_pipe,
+        pos: start_pos,
+    }];
+    let mut prev_pos: Option<(usize, usize)> = None;
+    // Keep following the first neighbour the current pipe connects to, other than the one we came
+    // from, until we're back next to the start tile
+    while let Some(&current) = loop_tiles.last() {
+        let next = DIRECTIONS.into_iter().find_map(|next_direction| {
+            let next_pos = tiles.offset(current.pos, next_direction.offset())?;
+            let next_pipe = tiles[next_pos];
+            (prev_pos != Some(next_pos)
+                && is_pipe_connected(current.pipe, next_pipe, next_direction))
+            .then_some(Entry {
+                pipe: next_pipe,
+                pos: next_pos,
+            })
+        });
+        match next {
+            Some(next) => {
+                prev_pos = Some(current.pos);
+                loop_tiles.push(next);
             }
+            None => break,
         }
     }
+    loop_tiles
 }
 
 fn is_pipe_connected(current_pipe: Pipe, next_pipe: Pipe, next_direction: Direction) -> bool {
     fn is_north_pipe(next_pipe: Pipe) -> bool {
-        match next_pipe {
-            Pipe::VerticalNS | Pipe::SW90Deg7Sym | Pipe::SE90DegFSym => true,
-            _ => false,
-        }
+        matches!(
+            next_pipe,
+            Pipe::VerticalNS | Pipe::SW90Deg7Sym | Pipe::SE90DegFSym
+        )
     }
     fn is_south_pipe(next_pipe: Pipe) -> bool {
-        match next_pipe {
-            Pipe::VerticalNS | Pipe::NE90DegLSym | Pipe::NW90DegJSym => true,
-            _ => false,
-        }
+        matches!(
+            next_pipe,
+            Pipe::VerticalNS | Pipe::NE90DegLSym | Pipe::NW90DegJSym
+        )
     }
     fn is_east_pipe(next_pipe: Pipe) -> bool {
-        match next_pipe {
-            Pipe::HorizontalEW | Pipe::NW90DegJSym | Pipe::SW90Deg7Sym => true,
-            _ => false,
-        }
+        matches!(
+            next_pipe,
+            Pipe::HorizontalEW | Pipe::NW90DegJSym | Pipe::SW90Deg7Sym
+        )
     }
     fn is_west_pipe(next_pipe: Pipe) -> bool {
-        match next_pipe {
-            Pipe::HorizontalEW | Pipe::NE90DegLSym | Pipe::SE90DegFSym => true,
-            _ => false,
-        }
+        matches!(
+            next_pipe,
+            Pipe::HorizontalEW | Pipe::NE90DegLSym | Pipe::SE90DegFSym
+        )
     }
     match current_pipe {
         Pipe::Ground => false,
@@ -257,218 +471,451 @@ fn is_pipe_connected(current_pipe: Pipe, next_pipe: Pipe, next_direction: Direct
     }
 }
 
-fn get_direction(
-    (current_row, current_col): (i32, i32),
-    (next_row, next_col): (i32, i32),
-) -> Option<Direction> {
-    if current_row - 1 == next_row && current_col == next_col {
-        return Some(Direction::N);
-    } else if current_row == next_row && current_col + 1 == next_col {
-        return Some(Direction::E);
-    } else if current_row + 1 == next_row && current_col == next_col {
-        return Some(Direction::S);
-    } else if current_row == next_row && current_col - 1 == next_col {
-        return Some(Direction::W);
-    }
-    None
+fn enclosed_by_loop(input: &Input) -> Result<usize> {
+    let classified_tiles = classify_tiles(input)?;
+    trace!("classified tiles:\n{}", render_tiles(&classified_tiles));
+    Ok(classified_tiles
+        .iter()
+        .filter(|&&tile| tile == Pipe::Inside)
+        .count())
 }
 
-fn get_enclosed_by_loop(input_file: &str) -> usize {
-    let input = parse_input(input_file);
+/// Tiles enclosed by the loop, from the area of the polygon its tiles trace out.
+///
+/// The shoelace formula gives the area of the polygon through the centres of the loop's tiles, and
+/// Pick's theorem, `area = interior + boundary / 2 - 1`, turns that into the number of whole tiles
+/// strictly inside it.
+pub fn enclosed_by_shoelace(input: &Input) -> Result<usize> {
+    let loop_tiles = trace_loop(&input.tiles, input.start, input.start_pipe);
+    let vertices = loop_tiles.iter().map(|tile| {
+        let (row, col) = tile.pos;
+        (row as i64, col as i64)
+    });
+    let twice_area = vertices
+        .clone()
+        .zip(vertices.cycle().skip(1))
+        .map(|((row, col), (next_row, next_col))| col * next_row - next_col * row)
+        .sum::<i64>()
+        .unsigned_abs() as usize;
+    let boundary = loop_tiles.len();
+    Ok((twice_area + 2).saturating_sub(boundary) / 2)
+}
 
-    let mut start_pos: Option<(usize, usize)> = None;
-    for ((row, col), pipe) in input.tiles.indexed_iter() {
-        if *pipe == Pipe::StartPos {
-            start_pos = Some((row, col));
+/// The loop's pipes, with every other tile marked `Inside` or `Outside` the loop by flood filling
+/// from the edge of the map.
+///
+/// To squeeze between pipes that touch without connecting, the fill runs on a grid of twice the
+/// resolution, with a border around it. Tile `(row, col)` sits at `(2 * row + 1, 2 * col + 1)`,
+/// and the cells between tiles are only walls where the loop connects the two.
+pub fn flood_fill_tiles(input: &Input) -> Result<Grid<Pipe>> {
+    let loop_tiles = trace_loop(&input.tiles, input.start, input.start_pipe);
+    let mut tiles = clean_tiles(&input.tiles, &loop_tiles);
+
+    let mut walls: Grid<bool> = Grid::new(2 * tiles.rows() + 1, 2 * tiles.cols() + 1);
+    for tile in &loop_tiles {
+        let (row, col) = tile.pos;
+        let pipe = tiles[tile.pos];
+        walls[(2 * row + 1, 2 * col + 1)] = true;
+        if opens(pipe, Direction::E) {
+            walls[(2 * row + 1, 2 * col + 2)] = true;
+        }
+        if opens(pipe, Direction::S) {
+            walls[(2 * row + 2, 2 * col + 1)] = true;
         }
     }
 
-    if let Some((row, col)) = start_pos {
-        let mut arena: Arena<Entry> = Arena::new();
-        let root_node = arena.new_node(Entry {
-            pipe: Pipe::StartPos,
-            pos: (row, col),
-            direction: None,
-        });
+    let mut outside: Grid<bool> = Grid::new(walls.rows(), walls.cols());
+    let mut stack = vec![(0, 0)];
+    outside[(0, 0)] = true;
+    while let Some(pos) = stack.pop() {
+        for next_pos in walls.neighbours_4(pos) {
+            if !walls[next_pos] && !outside[next_pos] {
+                outside[next_pos] = true;
+                stack.push(next_pos);
+            }
+        }
+    }
 
-        build_tree(&input.tiles, &mut arena, None, root_node);
+    for ((row, col), tile) in tiles.indexed_iter_mut() {
+        if *tile == Pipe::Ground {
+            *tile = if outside[(2 * row + 1, 2 * col + 1)] {
+                Pipe::Outside
+            } else {
+                Pipe::Inside
+            };
+        }
+    }
+    Ok(tiles)
+}
 
-        let cleaned_tiles = clean_tiles(&input.tiles, &arena, &root_node);
+/// Whether `pipe` has an opening towards `direction`.
+fn opens(pipe: Pipe, direction: Direction) -> bool {
+    matches!(
+        (pipe, direction),
+        (
+            Pipe::VerticalNS | Pipe::NE90DegLSym | Pipe::NW90DegJSym,
+            Direction::N
+        ) | (
+            Pipe::VerticalNS | Pipe::SW90Deg7Sym | Pipe::SE90DegFSym,
+            Direction::S
+        ) | (
+            Pipe::HorizontalEW | Pipe::NE90DegLSym | Pipe::SE90DegFSym,
+            Direction::E
+        ) | (
+            Pipe::HorizontalEW | Pipe::NW90DegJSym | Pipe::SW90Deg7Sym,
+            Direction::W
+        )
+    )
+}
+
+/// The loop's pipes, with every other tile marked `Inside` or `Outside` the loop.
+pub fn classify_tiles(input: &Input) -> Result<Grid<Pipe>> {
+    let loop_tiles = trace_loop(&input.tiles, input.start, input.start_pipe);
+    let mut classified_tiles = clean_tiles(&input.tiles, &loop_tiles);
 
-        // Was stumped by the squeezing between pipes (my initial raycasting approach using even-odd failed)
-        // Inspired by and/or shamelessly ripped off from https://nickymeuleman.netlify.app/garden/aoc2023-day10#final-code
+    // Was stumped by the squeezing between pipes (my initial raycasting approach using even-odd failed)
+    // Inspired by and/or shamelessly ripped off from https://nickymeuleman.netlify.app/garden/aoc2023-day10#final-code
+    for row in 0..classified_tiles.rows() {
         let mut inside = false;
-        cleaned_tiles
-            .flatten()
-            .into_iter()
-            .filter(|tile| match tile {
-                Pipe::Ground => inside,
-                Pipe::VerticalNS | Pipe::NW90DegJSym | Pipe::NE90DegLSym => {
-                    inside = !inside;
-                    false
-                }
-                _ => false,
-            })
-            .count()
-    } else {
-        panic!("Invalid start node");
+        for tile in classified_tiles.iter_row_mut(row) {
+            match tile {
+                Pipe::Ground => *tile = if inside { Pipe::Inside } else { Pipe::Outside },
+                Pipe::VerticalNS | Pipe::NW90DegJSym | Pipe::NE90DegLSym => inside = !inside,
+                _ => (),
+            }
+        }
     }
+    Ok(classified_tiles)
 }
 
-fn print_tiles(tiles: &Grid<Pipe>) {
+fn render_tiles(tiles: &Grid<Pipe>) -> String {
+    let mut rendered = String::new();
     for tile_row in tiles.iter_rows() {
         for tile in tile_row {
-            print!("{:#}", *tile as u8 as char);
+            rendered.push(*tile as u8 as char);
         }
-        print!("\n");
+        rendered.push('\n');
     }
+    rendered
 }
 
-fn clean_tiles(tiles: &Grid<Pipe>, arena: &Arena<Entry>, root_node: &NodeId) -> Grid<Pipe> {
-    let mut cleaned_tiles: Grid<Pipe> = Grid::new(tiles.rows(), tiles.cols());
-    for (pos, pipe) in tiles.indexed_iter() {
-        if *pipe == Pipe::StartPos {
-            cleaned_tiles[pos] = clean_start_tile(arena, root_node);
-        } else {
-            cleaned_tiles[pos] = clean_tile(arena, root_node, pos);
-        }
-    }
-    cleaned_tiles
+/// How [`render`] draws the pipe map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderStyle {
+    /// Draw pipes with Unicode box-drawing characters, rather than as they appear in the input
+    pub box_drawing: bool,
+    /// Colour tiles inside and outside the loop with ANSI escapes, and dim pipes off the loop
+    pub colour: bool,
 }
 
-fn clean_start_tile(arena: &Arena<Entry>, root_node: &NodeId) -> Pipe {
-    let start_edge_tiles: Vec<NodeId> = root_node.children(&arena).collect_vec();
-    let mut start_edge_nodes: Vec<Entry> = vec![];
-    for edge_tile in start_edge_tiles {
-        start_edge_nodes.push(*arena[edge_tile].get());
-    }
-    let count_north = start_edge_nodes
-        .iter()
-        .filter(|&t| t.direction.unwrap() == Direction::N)
-        .count();
-    let count_south = start_edge_nodes
-        .iter()
-        .filter(|&t| t.direction.unwrap() == Direction::S)
-        .count();
-    let count_east = start_edge_nodes
-        .iter()
-        .filter(|&t| t.direction.unwrap() == Direction::E)
-        .count();
-    let count_west = start_edge_nodes
-        .iter()
-        .filter(|&t| t.direction.unwrap() == Direction::W)
-        .count();
-
-    if count_north == 1 && count_west == 1 {
-        return Pipe::NW90DegJSym;
-    } else if count_north == 1 && count_east == 1 {
-        return Pipe::NE90DegLSym;
-    } else if count_south == 1 && count_west == 1 {
-        return Pipe::SW90Deg7Sym;
-    } else if count_south == 1 && count_east == 1 {
-        return Pipe::SE90DegFSym;
-    }
-    Pipe::Ground
-}
-
-fn clean_tile(arena: &Arena<Entry>, root_node: &NodeId, pos: (usize, usize)) -> Pipe {
-    let mut maybe_next = Some(NodeEdge::Start(*root_node));
-    while let Some(current) = maybe_next {
-        maybe_next = current.next_traverse(&arena);
-        let current = match current {
-            NodeEdge::Start(id) => id,
-            NodeEdge::End(_) => break,
-        };
+impl RenderStyle {
+    /// Box drawing in colour, for a terminal.
+    pub const TERMINAL: RenderStyle = RenderStyle {
+        box_drawing: true,
+        colour: true,
+    };
+
+    /// Plain ASCII, with tiles off the loop drawn as `I` (inside) or `O` (outside).
+    pub const ASCII: RenderStyle = RenderStyle {
+        box_drawing: false,
+        colour: false,
+    };
+}
 
-        let tile = arena[current].get();
-        if pos == tile.pos {
-            return tile.pipe;
+const BOLD: &str = "\x1b[1m";
+const INSIDE: &str = "\x1b[32m";
+const OUTSIDE: &str = "\x1b[34m";
+const INSIDE_DIM: &str = "\x1b[2;32m";
+const OUTSIDE_DIM: &str = "\x1b[2;34m";
+const RESET: &str = "\x1b[0m";
+
+/// Draws the pipe map with the loop picked out from the tiles inside and outside it.
+pub fn render(input: &Input, style: RenderStyle) -> Result<String> {
+    let classified_tiles = classify_tiles(input)?;
+    let mut rendered = String::new();
+    for (tile_row, classified_row) in input.tiles.iter_rows().zip(classified_tiles.iter_rows()) {
+        let mut current_colour = "";
+        for (&pipe, &classified) in tile_row.zip(classified_row) {
+            let (colour, tile) = render_tile(pipe, classified, style);
+            if style.colour && colour != current_colour {
+                rendered.push_str(RESET);
+                rendered.push_str(colour);
+                current_colour = colour;
+            }
+            rendered.push(tile);
         }
+        if style.colour {
+            rendered.push_str(RESET);
+        }
+        rendered.push('\n');
     }
-    Pipe::Ground
+    Ok(rendered)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// The colour and character for one tile, given its input `pipe` and how it was classified.
+fn render_tile(pipe: Pipe, classified: Pipe, style: RenderStyle) -> (&'static str, char) {
+    let inside = classified == Pipe::Inside;
+    let letter = if inside { 'I' } else { 'O' };
+    match (classified, pipe) {
+        (Pipe::Inside | Pipe::Outside, Pipe::Ground) => (
+            if inside { INSIDE } else { OUTSIDE },
+            if style.colour { '·' } else { letter },
+        ),
+        // Pipes that aren't part of the loop count as inside or outside too
+        (Pipe::Inside | Pipe::Outside, junk) => (
+            if inside { INSIDE_DIM } else { OUTSIDE_DIM },
+            if style.box_drawing {
+                box_char(junk, false)
+            } else if style.colour {
+                junk as u8 as char
+            } else {
+                letter
+            },
+        ),
+        (_, Pipe::StartPos) => (BOLD, 'S'),
+        (loop_pipe, _) if style.box_drawing => (BOLD, box_char(loop_pipe, true)),
+        (loop_pipe, _) => (BOLD, loop_pipe as u8 as char),
+    }
+}
 
-    #[test]
-    fn test_get_farthest_steps_test01() {
-        assert_eq!(4, get_farthest_steps("input/day10_test01.txt"));
+/// Styles [`classify_tiles`] for [`export`](crate::export): the loop in black and the tiles it
+/// encloses in green.
+pub fn cell_style(_: Pos, tile: &Pipe) -> Option<CellStyle> {
+    match tile {
+        Pipe::Inside => Some(CellStyle::square(Rgb(44, 160, 44))),
+        Pipe::Outside | Pipe::Ground => None,
+        _ => Some(CellStyle::square(Rgb::BLACK)),
     }
+}
 
-    #[test]
-    fn test_get_farthest_steps_test02() {
-        assert_eq!(8, get_farthest_steps("input/day10_test02.txt"));
+/// The box-drawing character for a pipe, heavy for the loop and light for everything else.
+fn box_char(pipe: Pipe, heavy: bool) -> char {
+    match (pipe, heavy) {
+        (Pipe::VerticalNS, false) => '│',
+        (Pipe::HorizontalEW, false) => '─',
+        (Pipe::NE90DegLSym, false) => '└',
+        (Pipe::NW90DegJSym, false) => '┘',
+        (Pipe::SW90Deg7Sym, false) => '┐',
+        (Pipe::SE90DegFSym, false) => '┌',
+        (Pipe::VerticalNS, true) => '┃',
+        (Pipe::HorizontalEW, true) => '━',
+        (Pipe::NE90DegLSym, true) => '┗',
+        (Pipe::NW90DegJSym, true) => '┛',
+        (Pipe::SW90Deg7Sym, true) => '┓',
+        (Pipe::SE90DegFSym, true) => '┏',
+        (pipe, _) => pipe as u8 as char,
     }
+}
 
-    #[test]
-    fn test_get_farthest_steps() {
-        assert_eq!(6823, get_farthest_steps("input/day10.txt"));
+/// Just the loop's pipes, at their positions, with the start tile replaced by the pipe under it.
+/// Everything off the loop is ground.
+fn clean_tiles(tiles: &Grid<Pipe>, loop_tiles: &[Entry]) -> Grid<Pipe> {
+    let mut cleaned_tiles: Grid<Pipe> = Grid::new(tiles.rows(), tiles.cols());
+    for tile in loop_tiles {
+        cleaned_tiles[tile.pos] = tile.pipe;
     }
+    cleaned_tiles
+}
 
-    #[test]
-    fn test_get_enclosed_by_loop_test01() {
-        assert_eq!(1, get_enclosed_by_loop("input/day10_test01.txt"));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example pipe maps in `input/`, whatever their number, in order.
+    fn example_files() -> Vec<String> {
+        let mut files: Vec<String> = std::fs::read_dir("input")
+            .unwrap()
+            .map(|entry| entry.unwrap().path().display().to_string())
+            .filter(|path| path.starts_with("input/day10_test"))
+            .collect();
+        files.sort();
+        assert!(!files.is_empty(), "no day 10 examples in input/");
+        files
     }
 
     #[test]
-    fn test_get_enclosed_by_loop_test02() {
-        assert_eq!(1, get_enclosed_by_loop("input/day10_test02.txt"));
+    fn test_parse_tiles_invalid_pipe() {
+        let err = parse_tiles(vec!["..F7.".to_string(), ".FJ|x".to_string()]).unwrap_err();
+        assert_eq!("<input>:2:5: invalid pipe `x`", err.to_string());
     }
 
     #[test]
-    fn test_get_enclosed_by_loop_test03() {
-        assert_eq!(4, get_enclosed_by_loop("input/day10_test03.txt"));
+    fn test_parse_tiles_ragged_rows() {
+        let err = parse_tiles(vec!["..F7.".to_string(), ".FJ".to_string()]).unwrap_err();
+        assert_eq!("<input>:2:4: expected 5 columns, found 3", err.to_string());
     }
 
     #[test]
-    fn test_get_enclosed_by_loop_test04() {
-        assert_eq!(4, get_enclosed_by_loop("input/day10_test04.txt"));
+    fn test_long_loop_on_small_stack() {
+        let cols = 5000;
+        let lines = vec![
+            format!("S{}7", "-".repeat(cols - 2)),
+            format!("|{}|", ".".repeat(cols - 2)),
+            format!("L{}J", "-".repeat(cols - 2)),
+        ];
+        let input = parse_lines(lines).unwrap();
+        let (farthest, enclosed) = std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(move || (farthest_steps(&input), enclosed_by_loop(&input)))
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(cols + 1, farthest.unwrap());
+        assert_eq!(cols - 2, enclosed.unwrap());
     }
 
     #[test]
-    fn test_get_enclosed_by_loop_test05() {
-        assert_eq!(8, get_enclosed_by_loop("input/day10_test05.txt"));
+    fn test_large_map() {
+        let size = 1000;
+        let mut lines = vec![format!("S{}7", "-".repeat(size - 2))];
+        lines.extend((0..size - 2).map(|_| format!("|{}|", ".".repeat(size - 2))));
+        lines.push(format!("L{}J", "-".repeat(size - 2)));
+        let input = parse_lines(lines).unwrap();
+        assert_eq!(2 * (size - 1), farthest_steps(&input).unwrap());
+        assert_eq!((size - 2) * (size - 2), enclosed_by_loop(&input).unwrap());
     }
 
     #[test]
-    fn test_get_enclosed_by_loop_test06() {
-        assert_eq!(10, get_enclosed_by_loop("input/day10_test06.txt"));
+    fn test_render() {
+        let input = parse(".....\n.S-7.\n.|.|.\n.L-J.\n..-..\n").unwrap();
+        assert_eq!(
+            "OOOOO\nOS-7O\nO|I|O\nOL-JO\nOOOOO\n",
+            render(&input, RenderStyle::ASCII).unwrap()
+        );
+        let rendered = render(
+            &input,
+            RenderStyle {
+                box_drawing: true,
+                colour: false,
+            },
+        )
+        .unwrap();
+        assert_eq!("OOOOO\nOS━┓O\nO┃I┃O\nO┗━┛O\nOO─OO\n", rendered);
     }
 
     #[test]
-    fn test_get_enclosed_by_loop_test07() {
-        assert_eq!(4, get_enclosed_by_loop("input/day10_test07.txt"));
+    fn test_render_colour() {
+        let input = parse("S7\nLJ\n").unwrap();
+        assert_eq!(
+            format!("{RESET}{BOLD}S┓{RESET}\n{RESET}{BOLD}┗┛{RESET}\n"),
+            render(&input, RenderStyle::TERMINAL).unwrap()
+        );
     }
 
     #[test]
-    fn test_get_enclosed_by_loop_test08() {
-        assert_eq!(4, get_enclosed_by_loop("input/day10_test08.txt"));
+    fn test_enclosed_by_shoelace_matches_parity() {
+        for file in example_files() {
+            let input = parse_file(&file).unwrap();
+            assert_eq!(
+                enclosed_by_loop(&input).unwrap(),
+                enclosed_by_shoelace(&input).unwrap(),
+                "{}",
+                file
+            );
+        }
+        let input = parse_file("input/day10.txt").unwrap();
+        assert_eq!(415, enclosed_by_shoelace(&input).unwrap());
     }
 
     #[test]
-    fn test_get_enclosed_by_loop_test09() {
-        assert_eq!(4, get_enclosed_by_loop("input/day10_test09.txt"));
+    fn test_flood_fill_tiles_matches_parity() {
+        for file in example_files() {
+            let input = parse_file(&file).unwrap();
+            assert_eq!(
+                classify_tiles(&input).unwrap(),
+                flood_fill_tiles(&input).unwrap(),
+                "{}",
+                file
+            );
+        }
     }
 
     #[test]
-    fn test_get_enclosed_by_loop_test10() {
-        assert_eq!(4, get_enclosed_by_loop("input/day10_test10.txt"));
+    fn test_validate_examples() {
+        for file in example_files()
+            .into_iter()
+            .chain(["input/day10.txt".to_string()])
+        {
+            let report = validate(&std::fs::read_to_string(&file).unwrap());
+            assert!(report.is_valid(), "{}: {:?}", file, report.problems);
+            assert!(report.start_pipe.is_some(), "{}", file);
+        }
     }
 
     #[test]
-    fn test_get_enclosed_by_loop_test11() {
-        assert_eq!(4, get_enclosed_by_loop("input/day10_test11.txt"));
+    fn test_validate_straight_start() {
+        let vertical = "F-7\n|.|\nS.|\nL-J\n";
+        assert_eq!(Some(Pipe::VerticalNS), validate(vertical).start_pipe);
+        let input = parse(vertical).unwrap();
+        assert_eq!(2, enclosed_by_loop(&input).unwrap());
+        assert_eq!(
+            classify_tiles(&input).unwrap(),
+            flood_fill_tiles(&input).unwrap()
+        );
+
+        let horizontal = "FS7\n|.|\nL-J\n";
+        assert_eq!(Some(Pipe::HorizontalEW), validate(horizontal).start_pipe);
+        assert_eq!(1, enclosed_by_loop(&parse(horizontal).unwrap()).unwrap());
     }
 
     #[test]
-    fn test_get_enclosed_by_loop_test12() {
-        assert_eq!(4, get_enclosed_by_loop("input/day10_test12.txt"));
+    fn test_validate_problems() {
+        let problems = |input| {
+            validate(input)
+                .problems
+                .iter()
+                .map(Problem::to_string)
+                .collect_vec()
+        };
+        assert_eq!(
+            vec![
+                "2:4: expected 5 columns, found 3",
+                "3:2: invalid pipe `x`",
+                "the tiles have no start tile `S`",
+            ],
+            problems("..F7.\n.FJ\n.x-7.\n")
+        );
+        assert_eq!(
+            vec!["2:3: 2 start tiles `S`, at 1:1, 2:3"],
+            problems("S7.\nLJS\n")
+        );
+        assert_eq!(
+            vec!["2:2: 4 pipes connect to the start tile, at 1:2, 2:1, 2:3, 3:2, and 0 pairs of them close a loop"],
+            problems(".|.\n-S-\n.|.\n")
+        );
+        assert_eq!(
+            vec!["3:3: 4 pipes connect to the start tile, at 2:3, 3:2, 3:4, 4:3, and 2 pairs of them close a loop"],
+            problems("F-7..\n|.|..\nL-S-7\n..|.|\n..L-J\n")
+        );
+        assert_eq!(
+            vec!["2:3: the loop from the start tile stops here, without getting back to it"],
+            problems("S-7\n|.|\nL-.\n")
+        );
+        assert_eq!(
+            vec!["1:1: the loop from the start tile stops here, without getting back to it"],
+            problems("S.\n..\n")
+        );
     }
 
     #[test]
-    fn test_get_enclosed_by_loop_steps() {
-        assert_eq!(415, get_enclosed_by_loop("input/day10.txt"));
+    fn test_parse_validates() {
+        let err = parse(".S-7.\n.|.|.\n.L-..\n").unwrap_err();
+        assert_eq!(
+            "<input>:2:4: the loop from the start tile stops here, without getting back to it",
+            err.to_string()
+        );
+        let err = parse(".....\n.F-7.\n.|.|.\n.L-J.\n").unwrap_err();
+        assert_eq!(
+            "<input>:1:1: the tiles have no start tile `S`",
+            err.to_string()
+        );
+
+        let input = parse_file("input/day10_test01.txt").unwrap();
+        assert_eq!(((1, 1), Pipe::SE90DegFSym), (input.start, input.start_pipe));
+
+        // Four pipes connect to the start tile, but only the ones to the east and south close a loop
+        let input = parse("..|..\n.-S-7\n..|.|\n..L-J\n").unwrap();
+        assert_eq!(Pipe::SE90DegFSym, input.start_pipe);
+        assert_eq!(4, farthest_steps(&input).unwrap());
     }
 }
@@ -1,230 +1,197 @@
 // https://adventofcode.com/2023/day/11
 
-use std::collections::VecDeque;
-
 use grid::Grid;
-use itertools::Itertools;
+use tracing::trace;
+
+use super::export::{CellStyle, Rgb};
+use super::utils::grid::{parse_grid, GridExt, Pos};
+use super::{Error, Result};
+
+solution!(Day11, usize);
+
+/// Sum of the distances between galaxy pairs, with empty rows and columns doubled.
+pub fn part1(input: &Input) -> Result<usize> {
+    Ok(sum_shortest_paths(input, 2))
+}
 
-use super::utils::get_lines;
+/// Sum of the distances between galaxy pairs, with empty rows and columns a million times larger.
+pub fn part2(input: &Input) -> Result<usize> {
+    Ok(sum_shortest_paths(input, 1000000))
+}
 
+/// One pixel of the image; `space` numbers the galaxies from 1, 0 is empty space.
 #[derive(Debug, Default, Clone, Copy)]
-struct GridEntry {
-    space: usize,
-    expansion: usize,
+pub struct GridEntry {
+    pub space: usize,
+    pub expanded: bool,
 }
 
+/// The image, and which of its rows and columns are empty and so expand.
 #[derive(Debug)]
-struct Input {
-    image: Grid<GridEntry>,
-    galaxy_pairs: Vec<(usize, usize)>,
+pub struct Input {
+    pub image: Grid<GridEntry>,
+    pub empty_rows: Vec<bool>,
+    pub empty_cols: Vec<bool>,
 }
 
-fn parse_input(input_file: &str, expansion: usize) -> Input {
-    let lines = get_lines(input_file);
-
+fn parse_lines(lines: Vec<String>) -> Result<Input> {
     let mut iter = lines.split(|e| e.is_empty());
 
-    let input = parse_image(iter.next().unwrap().to_owned(), expansion);
-    Input {
-        image: input.0,
-        galaxy_pairs: input.1,
-    }
+    parse_image(iter.next().unwrap_or_default().to_owned())
 }
 
-fn parse_image(
-    image_lines: Vec<String>,
-    expansion: usize,
-) -> (Grid<GridEntry>, Vec<(usize, usize)>) {
-    let mut image: Grid<GridEntry> = Grid::new(0, 0);
-
+fn parse_image(image_lines: Vec<String>) -> Result<Input> {
     let mut galaxy_count: usize = 0;
-    for image_line in image_lines {
-        if image_line.contains("#") {
-            let mut row: Vec<GridEntry> = vec![];
-            for image_pixel in image_line.chars() {
-                match image_pixel {
-                    '#' => {
-                        galaxy_count += 1;
-                        row.push(GridEntry {
-                            space: galaxy_count,
-                            expansion: 1,
-                        });
-                    }
-                    _ => row.push(GridEntry {
-                        space: 0,
-                        expansion: 1,
-                    }),
-                }
+    let mut image = parse_grid(
+        &image_lines,
+        |line_index, col, image_pixel| match image_pixel {
+            '#' => {
+                galaxy_count += 1;
+                Ok(GridEntry {
+                    space: galaxy_count,
+                    expanded: false,
+                })
             }
-            image.push_row(row);
-        } else {
-            image.push_row(vec![
-                GridEntry {
-                    space: 0,
-                    expansion: expansion
-                };
-                image_line.len()
-            ]);
-        }
+            '.' => Ok(GridEntry::default()),
+            _ => Err(Error::parse(
+                line_index + 1,
+                col + 1,
+                format!("invalid pixel `{}`", image_pixel),
+            )),
+        },
+    )?;
+
+    // Rows and columns without a single galaxy expand
+    let is_space = |pixel: &GridEntry| pixel.space == 0;
+    let mut empty_rows = vec![false; image.rows()];
+    for span in image.row_spans(is_space) {
+        empty_rows[span.index] |= span.range.len() == image.cols();
     }
-
-    let mut expanded_image: Grid<GridEntry> = Grid::new(0, 0);
-
-    for image_col in image.iter_cols() {
-        let image_col_vec: Vec<GridEntry> = image_col.cloned().collect();
-        if image_col_vec.iter().all(|&x| x.space == 0) {
-            expanded_image.push_col(vec![
-                GridEntry {
-                    space: 0,
-                    expansion: expansion
-                };
-                image_col_vec.len()
-            ]);
-        } else {
-            expanded_image.push_col(image_col_vec);
-        }
+    let mut empty_cols = vec![false; image.cols()];
+    for span in image.col_spans(is_space) {
+        empty_cols[span.index] |= span.range.len() == image.rows();
+    }
+    for ((row, col), pixel) in image.indexed_iter_mut() {
+        pixel.expanded = empty_rows[row] || empty_cols[col];
     }
 
-    let galaxy_pairs: Vec<(usize, usize)> =
-        (1..galaxy_count + 1).tuple_combinations().collect_vec();
-
-    (expanded_image, galaxy_pairs)
+    Ok(Input {
+        image,
+        empty_rows,
+        empty_cols,
+    })
 }
 
-fn print_image(image: &Grid<GridEntry>) {
+fn render_image(image: &Grid<GridEntry>) -> String {
+    let mut rendered = String::new();
     for image_row in image.iter_rows() {
         for image_pixel in image_row {
             if image_pixel.space != 0 {
-                print!("#");
-            } else if image_pixel.expansion > 1 {
-                print!("@");
+                rendered.push('#');
+            } else if image_pixel.expanded {
+                rendered.push('@');
             } else {
-                print!("{:#}", image_pixel.space);
+                rendered.push_str(&image_pixel.space.to_string());
             }
         }
-        print!("\n");
+        rendered.push('\n');
     }
+    rendered
 }
 
-fn get_sum_shortest_paths(input_file: &str, expansion: usize) -> usize {
-    let mut sum_shortest_paths = 0;
-    let input = parse_input(input_file, expansion);
-
-    //print_image(&input.image);
-
-    for (source, target) in input.galaxy_pairs {
-        if let Some(shortest_path) = get_shortest_path(&input.image, source, target) {
-            /*println!(
-                "shortest path for {}, {} is: {}",
-                source, target, shortest_path
-            );*/
-            sum_shortest_paths += shortest_path;
-        }
-    }
-
-    sum_shortest_paths
+pub(crate) fn sum_shortest_paths(input: &Input, expansion: usize) -> usize {
+    let image = &input.image;
+    trace!("image:\n{}", render_image(image));
+
+    let rows = expanded_coords(input.empty_rows.iter().copied(), expansion);
+    let cols = expanded_coords(input.empty_cols.iter().copied(), expansion);
+
+    // The shortest path between two galaxies is their Manhattan distance in the expanded image,
+    // which splits into a sum over rows and a sum over columns
+    let (galaxy_rows, galaxy_cols): (Vec<usize>, Vec<usize>) = image
+        .indexed_iter()
+        .filter(|(_, pixel)| pixel.space != 0)
+        .map(|((row, col), _)| (rows[row], cols[col]))
+        .unzip();
+    sum_distances(galaxy_rows) + sum_distances(galaxy_cols)
 }
 
-#[derive(Debug, Copy, Clone)]
-struct PixelData {
-    pos: (usize, usize),
-    dist: usize,
+/// Where each row or column starts once every empty one is `expansion` times as wide.
+fn expanded_coords(is_empty: impl Iterator<Item = bool>, expansion: usize) -> Vec<usize> {
+    let mut coord = 0;
+    is_empty
+        .map(|is_empty| {
+            let start = coord;
+            coord += if is_empty { expansion } else { 1 };
+            start
+        })
+        .collect()
 }
 
-fn get_shortest_path(image: &Grid<GridEntry>, source: usize, target: usize) -> Option<usize> {
-    let mut source_pixel_data: PixelData = PixelData {
-        pos: (0, 0),
-        dist: 0,
-    };
-    let mut visited: Grid<bool> = Grid::new(image.rows(), image.cols());
-
-    for ((row, col), pixel) in image.indexed_iter() {
-        if pixel.space == source {
-            source_pixel_data.pos = (row, col);
-        }
-    }
+/// The sum of the distances between every pair of `coords`.
+fn sum_distances(mut coords: Vec<usize>) -> usize {
+    coords.sort_unstable();
+    // Once sorted, each coord is the far end of its distance to every coord before it
+    let mut prefix_sum = 0;
+    coords
+        .iter()
+        .enumerate()
+        .map(|(index, &coord)| {
+            let distances = coord * index - prefix_sum;
+            prefix_sum += coord;
+            distances
+        })
+        .sum()
+}
 
-    let mut queue: VecDeque<PixelData> = VecDeque::new();
-    queue.push_back(source_pixel_data);
-    visited[(source_pixel_data.pos.0, source_pixel_data.pos.1)] = true;
-
-    while !queue.is_empty() {
-        if let Some(pixel_data) = queue.pop_front() {
-            let (row, col) = pixel_data.pos;
-
-            if let Some(pixel) = image.get(row, col) {
-                if pixel.space == target {
-                    return Some(pixel_data.dist);
-                }
-
-                // Moving up
-                if row as i32 - 1 >= 0 && !visited[(row - 1, col)] {
-                    queue.push_back(PixelData {
-                        pos: (row - 1, col),
-                        dist: pixel_data.dist + pixel.expansion,
-                    });
-                    visited[(row - 1, col)] = true;
-                }
-
-                // Moving down
-                if row as i32 + 1 < image.rows() as i32 && !visited[(row + 1, col)] {
-                    queue.push_back(PixelData {
-                        pos: (row + 1, col),
-                        dist: pixel_data.dist + pixel.expansion,
-                    });
-                    visited[(row + 1, col)] = true;
-                }
-
-                // Moving left
-                if col as i32 - 1 >= 0 && !visited[(row, col - 1)] {
-                    queue.push_back(PixelData {
-                        pos: (row, col - 1),
-                        dist: pixel_data.dist + pixel.expansion,
-                    });
-                    visited[(row, col - 1)] = true;
-                }
-
-                // Moving right
-                if col as i32 + 1 < image.cols() as i32 && !visited[(row, col + 1)] {
-                    queue.push_back(PixelData {
-                        pos: (row, col + 1),
-                        dist: pixel_data.dist + pixel.expansion,
-                    });
-                    visited[(row, col + 1)] = true;
-                }
-            }
-        }
+/// Styles the image for [`export`](crate::export): galaxies as dots, with the empty rows and columns
+/// that expand shaded.
+pub fn cell_style(_: Pos, pixel: &GridEntry) -> Option<CellStyle> {
+    if pixel.space != 0 {
+        Some(CellStyle::dot(Rgb::BLACK))
+    } else if pixel.expanded {
+        Some(CellStyle::square(Rgb(220, 220, 220)))
+    } else {
+        None
     }
-
-    None
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use itertools::Itertools;
 
     #[test]
-    fn test_get_sum_shortest_paths_test01() {
-        assert_eq!(374, get_sum_shortest_paths("input/day11_test01.txt", 2));
-    }
-
-    #[test]
-    fn test_get_sum_shortest_paths() {
-        assert_eq!(10313550, get_sum_shortest_paths("input/day11.txt", 2));
-    }
-
-    #[test]
-    fn test_get_sum_shortest_paths_part02_test01() {
-        assert_eq!(1030, get_sum_shortest_paths("input/day11_test01.txt", 10));
+    fn test_sum_distances() {
+        let coords = vec![7, 0, 3, 3, 12, 5];
+        let pairwise: usize = coords
+            .iter()
+            .tuple_combinations()
+            .map(|(a, b): (&usize, &usize)| a.abs_diff(*b))
+            .sum();
+        assert_eq!(pairwise, sum_distances(coords));
     }
 
     #[test]
-    fn test_get_sum_shortest_paths_part02_test02() {
-        assert_eq!(8410, get_sum_shortest_paths("input/day11_test01.txt", 100));
+    fn test_many_galaxies() {
+        // Galaxies on every other row, with an empty row after each
+        let rows = 20_000;
+        let empty_rows = (0..rows).map(|row| row % 2 == 1);
+        let coords = expanded_coords(empty_rows, 1_000_000);
+        let galaxy_rows: Vec<usize> = coords.into_iter().step_by(2).collect();
+        let galaxies = galaxy_rows.len();
+        let step = 1 + 1_000_000;
+        // Each pair i < j is (j - i) * step apart
+        let expected: usize = (1..galaxies).map(|d| (galaxies - d) * d * step).sum();
+        assert_eq!(expected, sum_distances(galaxy_rows));
     }
 
     #[test]
-    fn test_get_sum_shortest_paths_part02() {
-        assert_eq!(611998089572, get_sum_shortest_paths("input/day11.txt", 1000000));
+    fn test_parse_empty_rows_and_cols() {
+        let input = parse("#..\n...\n..#\n").unwrap();
+        assert_eq!(vec![false, true, false], input.empty_rows);
+        assert_eq!(vec![false, true, false], input.empty_cols);
+        assert!(input.image[(0, 1)].expanded && input.image[(1, 0)].expanded);
+        assert!(!input.image[(2, 0)].expanded);
     }
 }
@@ -0,0 +1,119 @@
+use std::{error, fmt, io};
+
+/// Errors from fetching, reading or parsing a puzzle input, solving one that has no answer, or
+/// writing files out.
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: String,
+        source: io::Error,
+    },
+    Write {
+        path: String,
+        source: io::Error,
+    },
+    UnknownFormat {
+        path: String,
+    },
+    Parse {
+        path: Option<String>,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    NoSolution(String),
+    Http {
+        url: String,
+        message: String,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// A parse error at a 1-based line and column, not yet tied to a file.
+    pub(crate) fn parse(line: usize, column: usize, message: impl Into<String>) -> Error {
+        Error::Parse {
+            path: None,
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Attaches the input file to a parse error.
+    pub(crate) fn in_file(self, input_file: &str) -> Error {
+        match self {
+            Error::Parse {
+                path: None,
+                line,
+                column,
+                message,
+            } => Error::Parse {
+                path: Some(input_file.to_string()),
+                line,
+                column,
+                message,
+            },
+            err => err,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "couldn't read {}: {}", path, source),
+            Error::Write { path, source } => write!(f, "couldn't write {}: {}", path, source),
+            Error::UnknownFormat { path } => write!(
+                f,
+                "unknown picture format for {}: expected a `.svg` or `.ppm` file",
+                path
+            ),
+            Error::Parse {
+                path,
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "{}:{}:{}: {}",
+                path.as_deref().unwrap_or("<input>"),
+                line,
+                column,
+                message
+            ),
+            Error::NoSolution(message) => write!(f, "no solution: {}", message),
+            Error::Http { url, message } => write!(f, "couldn't fetch {}: {}", url, message),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } | Error::Write { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_parse_error() {
+        let err = Error::parse(3, 7, "invalid cube color `purple`").in_file("input/day02.txt");
+        assert_eq!(
+            "input/day02.txt:3:7: invalid cube color `purple`",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_display_parse_error_without_file() {
+        let err = Error::parse(1, 1, "missing `Time:` line");
+        assert_eq!("<input>:1:1: missing `Time:` line", err.to_string());
+    }
+}
@@ -0,0 +1,49 @@
+// One test per entry of input/examples.toml, generated by build.rs.
+
+use super::{day05, day07, day11, solve, Day05, Day07, Day11, Result, Solution};
+
+struct Example {
+    file: &'static str,
+    day: u8,
+    part: u8,
+    use_joker: Option<bool>,
+    seeds_as_ranges: Option<bool>,
+    expansion: Option<usize>,
+    expected: &'static str,
+}
+
+fn solve_example(example: &Example) -> Result<String> {
+    let file = example.file;
+    let answer = match (
+        example.day,
+        example.use_joker,
+        example.seeds_as_ranges,
+        example.expansion,
+    ) {
+        (_, None, None, None) => solve(example.day, example.part, file)?,
+        (5, None, Some(seeds_as_ranges), None) => {
+            day05::lowest_location(&Day05::parse_file(file)?, seeds_as_ranges)?.to_string()
+        }
+        (7, Some(use_joker), None, None) => {
+            day07::total_winnings(&Day07::parse_file(file)?, use_joker).to_string()
+        }
+        (11, None, None, Some(expansion)) => {
+            day11::sum_shortest_paths(&Day11::parse_file(file)?, expansion).to_string()
+        }
+        (day, ..) => panic!("{}: these options don't apply to day {}", file, day),
+    };
+    Ok(answer)
+}
+
+fn run_example(example: &Example) {
+    match solve_example(example) {
+        Ok(answer) => assert_eq!(
+            example.expected, answer,
+            "{} part {}",
+            example.file, example.part
+        ),
+        Err(err) => panic!("{} part {}: {}", example.file, example.part, err),
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/examples.rs"));
@@ -0,0 +1,264 @@
+// Pictures of grid puzzles, as SVG or binary PPM (https://netpbm.sourceforge.net/doc/ppm.html)
+
+use std::{fmt::Write as _, fs, path::Path};
+
+use grid::Grid;
+
+use super::utils::grid::Pos;
+use super::{Error, Result};
+
+/// A colour, 8 bits per channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+
+    fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// How much of its cell a styled cell fills.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Square,
+    Dot,
+}
+
+/// How to draw one cell. Cells without a style show the background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellStyle {
+    pub colour: Rgb,
+    pub shape: Shape,
+}
+
+impl CellStyle {
+    pub fn square(colour: Rgb) -> CellStyle {
+        CellStyle {
+            colour,
+            shape: Shape::Square,
+        }
+    }
+
+    pub fn dot(colour: Rgb) -> CellStyle {
+        CellStyle {
+            colour,
+            shape: Shape::Dot,
+        }
+    }
+}
+
+/// Draws grids as pictures, styling each cell with a callback.
+///
+/// ```no_run
+/// # use aoc2023::export::{CellStyle, Exporter, Rgb};
+/// # let input = aoc2023::day11::parse("#.\n..\n").unwrap();
+/// Exporter::new()
+///     .cell_size(4)
+///     .write(&input.image, "day11.svg", |_, pixel| {
+///         (pixel.space != 0).then_some(CellStyle::dot(Rgb::BLACK))
+///     })
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Exporter {
+    cell_size: usize,
+    background: Rgb,
+}
+
+impl Default for Exporter {
+    fn default() -> Exporter {
+        Exporter::new()
+    }
+}
+
+impl Exporter {
+    pub fn new() -> Exporter {
+        Exporter {
+            cell_size: 8,
+            background: Rgb::WHITE,
+        }
+    }
+
+    /// Width and height of each cell, in pixels.
+    pub fn cell_size(mut self, cell_size: usize) -> Exporter {
+        self.cell_size = cell_size.max(1);
+        self
+    }
+
+    pub fn background(mut self, background: Rgb) -> Exporter {
+        self.background = background;
+        self
+    }
+
+    /// Writes the picture to `path`, as SVG or PPM depending on whether it ends in `.svg` or `.ppm`.
+    pub fn write<T>(
+        &self,
+        grid: &Grid<T>,
+        path: impl AsRef<Path>,
+        style: impl Fn(Pos, &T) -> Option<CellStyle>,
+    ) -> Result<()> {
+        let path = path.as_ref();
+        let picture = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("svg") => self.svg(grid, style).into_bytes(),
+            Some("ppm") => self.ppm(grid, style),
+            _ => {
+                return Err(Error::UnknownFormat {
+                    path: path.display().to_string(),
+                })
+            }
+        };
+        fs::write(path, picture).map_err(|source| Error::Write {
+            path: path.display().to_string(),
+            source,
+        })
+    }
+
+    pub fn svg<T>(&self, grid: &Grid<T>, style: impl Fn(Pos, &T) -> Option<CellStyle>) -> String {
+        let size = self.cell_size;
+        let (width, height) = (grid.cols() * size, grid.rows() * size);
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+        );
+        let _ = writeln!(
+            svg,
+            "<rect width=\"{width}\" height=\"{height}\" fill=\"{}\"/>",
+            self.background.hex()
+        );
+        for ((row, col), cell) in grid.indexed_iter() {
+            let Some(cell_style) = style((row, col), cell) else {
+                continue;
+            };
+            let (x, y) = (col * size, row * size);
+            let fill = cell_style.colour.hex();
+            let _ = match cell_style.shape {
+                Shape::Square => writeln!(
+                    svg,
+                    "<rect x=\"{x}\" y=\"{y}\" width=\"{size}\" height=\"{size}\" fill=\"{fill}\"/>"
+                ),
+                Shape::Dot => writeln!(
+                    svg,
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{fill}\"/>",
+                    x as f64 + size as f64 / 2.0,
+                    y as f64 + size as f64 / 2.0,
+                    size as f64 / 2.0
+                ),
+            };
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn ppm<T>(&self, grid: &Grid<T>, style: impl Fn(Pos, &T) -> Option<CellStyle>) -> Vec<u8> {
+        let size = self.cell_size;
+        let (width, height) = (grid.cols() * size, grid.rows() * size);
+        let mut pixels: Grid<Rgb> = Grid::init(height, width, self.background);
+        for ((row, col), cell) in grid.indexed_iter() {
+            let Some(cell_style) = style((row, col), cell) else {
+                continue;
+            };
+            for dy in 0..size {
+                for dx in 0..size {
+                    if cell_style.shape == Shape::Square || in_dot(size, dx, dy) {
+                        pixels[(row * size + dy, col * size + dx)] = cell_style.colour;
+                    }
+                }
+            }
+        }
+
+        let mut ppm = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        for &Rgb(red, green, blue) in pixels.iter() {
+            ppm.extend([red, green, blue]);
+        }
+        ppm
+    }
+}
+
+/// Whether the centre of pixel `(dx, dy)` is inside the circle filling a `size` square cell.
+fn in_dot(size: usize, dx: usize, dy: usize) -> bool {
+    // In half-pixel units, so the centres and radius are whole numbers
+    let (x, y, size) = ((2 * dx + 1) as isize, (2 * dy + 1) as isize, size as isize);
+    (x - size).pow(2) + (y - size).pow(2) <= size.pow(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<char> {
+        Grid::from_vec(vec!['#', '.', '.', 'o'], 2)
+    }
+
+    fn style(_: Pos, cell: &char) -> Option<CellStyle> {
+        match cell {
+            '#' => Some(CellStyle::square(Rgb(255, 0, 0))),
+            'o' => Some(CellStyle::dot(Rgb(0, 0, 255))),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_svg() {
+        assert_eq!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20\" height=\"20\" viewBox=\"0 0 20 20\">\n\
+             <rect width=\"20\" height=\"20\" fill=\"#ffffff\"/>\n\
+             <rect x=\"0\" y=\"0\" width=\"10\" height=\"10\" fill=\"#ff0000\"/>\n\
+             <circle cx=\"15\" cy=\"15\" r=\"5\" fill=\"#0000ff\"/>\n\
+             </svg>\n",
+            Exporter::new().cell_size(10).svg(&grid(), style)
+        );
+    }
+
+    #[test]
+    fn test_ppm() {
+        let ppm = Exporter::new().cell_size(1).ppm(&grid(), style);
+        let mut expected = b"P6\n2 2\n255\n".to_vec();
+        expected.extend([255, 0, 0, 255, 255, 255, 255, 255, 255, 0, 0, 255]);
+        assert_eq!(expected, ppm);
+    }
+
+    #[test]
+    fn test_ppm_dot() {
+        let ppm = Exporter::new()
+            .cell_size(4)
+            .background(Rgb::BLACK)
+            .ppm(&Grid::from_vec(vec!['o'], 1), style);
+        let pixels: Vec<bool> = ppm[b"P6\n4 4\n255\n".len()..]
+            .chunks(3)
+            .map(|pixel| pixel != [0, 0, 0])
+            .collect();
+        #[rustfmt::skip]
+        assert_eq!(
+            vec![
+                false, true, true, false,
+                true, true, true, true,
+                true, true, true, true,
+                false, true, true, false,
+            ],
+            pixels
+        );
+    }
+
+    #[test]
+    fn test_write_unknown_format() {
+        let err = Exporter::new()
+            .write(&grid(), "picture.png", style)
+            .unwrap_err();
+        assert_eq!(
+            "unknown picture format for picture.png: expected a `.svg` or `.ppm` file",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_write_failure() {
+        let err = Exporter::new()
+            .write(&grid(), "missing/dir/picture.svg", style)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("couldn't write missing/dir/picture.svg: "));
+    }
+}
@@ -0,0 +1,254 @@
+// https://adventofcode.com/about#faq_automation
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use super::{Error, Result};
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// Identifies us to the Advent of Code servers, as their automation guidelines ask.
+pub const USER_AGENT: &str = concat!(
+    "aoc2023/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://github.com/McDamon/aoc2023)"
+);
+
+/// Environment variable holding the adventofcode.com `session` cookie.
+pub const SESSION_VAR: &str = "AOC_SESSION";
+
+/// The session token from `AOC_SESSION`, if it is set.
+pub fn session_from_env() -> Option<String> {
+    match std::env::var(SESSION_VAR) {
+        Ok(session) if !session.trim().is_empty() => Some(session),
+        _ => None,
+    }
+}
+
+/// An HTTP agent that sends our User-Agent with every request.
+pub(crate) fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .user_agent(USER_AGENT)
+        .timeout(Duration::from_secs(30))
+        .build()
+}
+
+/// Downloads puzzle inputs into a local cache directory, one file per day.
+///
+/// A day is only downloaded when its file is missing. When the server refuses a day, that's
+/// recorded next to where its file would be, and the day isn't asked for again until the record is
+/// deleted.
+pub struct Fetcher {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+    input_dir: PathBuf,
+}
+
+impl Fetcher {
+    pub fn new(session: &str) -> Fetcher {
+        Fetcher {
+            agent: agent(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            session: session.trim().to_string(),
+            input_dir: PathBuf::from("input"),
+        }
+    }
+
+    pub fn base_url(mut self, base_url: &str) -> Fetcher {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn input_dir(mut self, input_dir: impl Into<PathBuf>) -> Fetcher {
+        self.input_dir = input_dir.into();
+        self
+    }
+
+    pub fn input_file(&self, day: u8) -> PathBuf {
+        self.input_dir.join(format!("day{:02}.txt", day))
+    }
+
+    /// Where the server's refusal of `day` is recorded, if it has refused it.
+    pub fn refused_file(&self, day: u8) -> PathBuf {
+        self.input_dir.join(format!("day{:02}.refused", day))
+    }
+
+    pub fn input_url(&self, day: u8) -> String {
+        format!("{}/2023/day/{}/input", self.base_url, day)
+    }
+
+    /// Path of the cached input for `day`, downloading it first if it is missing.
+    pub fn fetch(&self, day: u8) -> Result<PathBuf> {
+        let input_file = self.input_file(day);
+        if input_file.exists() {
+            return Ok(input_file);
+        }
+        if !(1..=25).contains(&day) {
+            return Err(Error::NoSolution(format!("there is no day {}", day)));
+        }
+        // Asking again won't change the server's answer, so only the user can decide to retry
+        let refused_file = self.refused_file(day);
+        if let Ok(refusal) = fs::read_to_string(&refused_file) {
+            return Err(Error::Http {
+                url: self.input_url(day),
+                message: format!(
+                    "{} when last requested, delete {} to ask again",
+                    refusal.trim(),
+                    refused_file.display()
+                ),
+            });
+        }
+
+        let input = self.download(day)?;
+        write_input(&input_file, &input)?;
+        Ok(input_file)
+    }
+
+    fn download(&self, day: u8) -> Result<String> {
+        let url = self.input_url(day);
+        let http_error = |message: String| Error::Http {
+            url: url.clone(),
+            message,
+        };
+
+        // Only the server's own answers are recorded, as a dropped connection may well work next time
+        let refused = |message: String| {
+            self.record_refusal(day, &message);
+            http_error(message)
+        };
+
+        let response = self
+            .agent
+            .get(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .call()
+            .map_err(|err| match err {
+                ureq::Error::Status(status, response) => refused(format!(
+                    "server returned {} {}",
+                    status,
+                    response.status_text()
+                )),
+                ureq::Error::Transport(transport) => http_error(transport.to_string()),
+            })?;
+
+        let input = response
+            .into_string()
+            .map_err(|err| http_error(err.to_string()))?;
+        if input.is_empty() {
+            return Err(refused("server returned an empty input".to_string()));
+        }
+        Ok(input)
+    }
+
+    /// Best effort, as the refusal itself is the error worth reporting.
+    fn record_refusal(&self, day: u8, message: &str) {
+        let _ = fs::create_dir_all(&self.input_dir)
+            .and_then(|()| fs::write(self.refused_file(day), message));
+    }
+}
+
+/// Writes through a temporary file so an interrupted download never leaves a partial input.
+fn write_input(input_file: &Path, input: &str) -> Result<()> {
+    let write_error = |path: &Path| {
+        let path = path.display().to_string();
+        move |source| Error::Write { path, source }
+    };
+
+    if let Some(input_dir) = input_file.parent() {
+        fs::create_dir_all(input_dir).map_err(write_error(input_dir))?;
+    }
+    let partial_file = input_file.with_extension("txt.part");
+    fs::write(&partial_file, input).map_err(write_error(&partial_file))?;
+    fs::rename(&partial_file, input_file).map_err(write_error(input_file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::aoc::testing::{stub_server, temp_path};
+
+    #[test]
+    fn test_fetch_once() {
+        let (base_url, requests) = stub_server(vec![("200 OK", "1abc2\n".to_string())]);
+        let input_dir = temp_path("fetch-once");
+        let fetcher = Fetcher::new("secret\n")
+            .base_url(&base_url)
+            .input_dir(&input_dir);
+
+        let input_file = fetcher.fetch(1).unwrap();
+        assert_eq!(input_dir.join("day01.txt"), input_file);
+        assert_eq!("1abc2\n", fs::read_to_string(&input_file).unwrap());
+        assert_eq!(input_file, fetcher.fetch(1).unwrap());
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(1, requests.len());
+        assert!(requests[0]
+            .head
+            .starts_with("GET /2023/day/1/input HTTP/1.1\r\n"));
+        assert!(requests[0].head.contains("session=secret\r\n"));
+        assert!(requests[0].head.contains(USER_AGENT));
+
+        fs::remove_dir_all(input_dir).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_existing_input() {
+        let (base_url, requests) = stub_server(vec![("200 OK", "unused\n".to_string())]);
+        let input_dir = temp_path("fetch-existing");
+        fs::create_dir_all(&input_dir).unwrap();
+        fs::write(input_dir.join("day05.txt"), "seeds: 1\n").unwrap();
+        let fetcher = Fetcher::new("secret")
+            .base_url(&base_url)
+            .input_dir(&input_dir);
+
+        let input_file = fetcher.fetch(5).unwrap();
+        assert_eq!("seeds: 1\n", fs::read_to_string(input_file).unwrap());
+        assert!(requests.lock().unwrap().is_empty());
+
+        fs::remove_dir_all(input_dir).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_error_status() {
+        let not_found = ("404 Not Found", "Not found".to_string());
+        let (base_url, requests) = stub_server(vec![not_found.clone(), not_found]);
+        let input_dir = temp_path("fetch-error");
+        let fetcher = Fetcher::new("secret")
+            .base_url(&base_url)
+            .input_dir(&input_dir);
+
+        let err = fetcher.fetch(25).unwrap_err();
+        assert_eq!(
+            format!(
+                "couldn't fetch {}/2023/day/25/input: server returned 404 Not Found",
+                base_url
+            ),
+            err.to_string()
+        );
+        assert!(!input_dir.join("day25.txt").exists());
+
+        // A refused day is not asked for again, even by another fetcher, until its record is deleted
+        let fetcher = Fetcher::new("secret")
+            .base_url(&base_url)
+            .input_dir(&input_dir);
+        let err = fetcher.fetch(25).unwrap_err();
+        assert_eq!(
+            format!(
+                "couldn't fetch {}/2023/day/25/input: server returned 404 Not Found when last requested, delete {} to ask again",
+                base_url,
+                input_dir.join("day25.refused").display()
+            ),
+            err.to_string()
+        );
+        assert_eq!(1, requests.lock().unwrap().len());
+
+        fs::remove_file(input_dir.join("day25.refused")).unwrap();
+        assert!(matches!(fetcher.fetch(25), Err(Error::Http { .. })));
+        assert_eq!(2, requests.lock().unwrap().len());
+    }
+}
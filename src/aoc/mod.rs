@@ -1,12 +1,243 @@
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod utils;
\ No newline at end of file
+use std::{fmt::Display, io::BufRead};
+
+use tracing::{debug_span, info, info_span};
+
+pub use day01::Day01;
+pub use day02::Day02;
+pub use day03::Day03;
+pub use day04::Day04;
+pub use day05::Day05;
+pub use day06::Day06;
+pub use day07::Day07;
+pub use day08::Day08;
+pub use day09::Day09;
+pub use day10::Day10;
+pub use day11::Day11;
+pub use error::{Error, Result};
+pub use fetch::{session_from_env, Fetcher, SESSION_VAR};
+pub use submit::{Record, Submission, Submitter, Verdict};
+
+/// Declares a day's [`Solution`] type, forwarding to the day module's own `parse_lines`, `part1`
+/// and `part2`, along with the module's `parse` and `parse_file` shorthands.
+macro_rules! solution {
+    ($day:ident, $answer:ty) => {
+        pub struct $day;
+
+        impl $crate::aoc::Solution for $day {
+            type Input = Input;
+            type Answer = $answer;
+
+            fn parse_lines(lines: Vec<String>) -> $crate::aoc::Result<Input> {
+                parse_lines(lines)
+            }
+
+            fn part1(input: &Input) -> $crate::aoc::Result<$answer> {
+                part1(input)
+            }
+
+            fn part2(input: &Input) -> $crate::aoc::Result<$answer> {
+                part2(input)
+            }
+        }
+
+        /// Parses the puzzle input from its text.
+        pub fn parse(input: &str) -> $crate::aoc::Result<Input> {
+            <$day as $crate::aoc::Solution>::parse_str(input)
+        }
+
+        /// Parses the puzzle input from the file at `input_file`.
+        pub fn parse_file(input_file: &str) -> $crate::aoc::Result<Input> {
+            <$day as $crate::aoc::Solution>::parse_file(input_file)
+        }
+    };
+}
+
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+mod error;
+#[cfg(all(test, examples_manifest))]
+mod examples;
+pub mod export;
+mod fetch;
+mod submit;
+#[cfg(test)]
+mod testing;
+mod utils;
+
+/// A day's puzzle, split into parsing the input and solving each part from the parsed input.
+///
+/// Days only parse lines; reading them from a file, a reader or a string is shared.
+pub trait Solution {
+    type Input;
+    type Answer: Display;
+
+    fn parse_lines(lines: Vec<String>) -> Result<Self::Input>;
+
+    fn parse_str(input: &str) -> Result<Self::Input> {
+        Self::parse_lines(input.lines().map(String::from).collect())
+    }
+
+    fn parse_reader<R: BufRead>(reader: R) -> Result<Self::Input> {
+        Self::parse_lines(utils::read_lines(reader, "<input>")?)
+    }
+
+    /// Reads and parses the file at `input_file`, naming it in any parse error.
+    fn parse_file(input_file: &str) -> Result<Self::Input> {
+        let lines = utils::get_lines(input_file)?;
+        Self::parse_lines(lines).map_err(|err| err.in_file(input_file))
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer>;
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer>;
+}
+
+/// Registry entry for a day, with its solution erased behind a function pointer.
+pub struct Day {
+    pub day: u8,
+    pub solve: fn(&str, u8) -> Result<String>,
+    pub solve_str: fn(&str, u8) -> Result<String>,
+}
+
+impl Day {
+    const fn new<S: Solution>(day: u8) -> Day {
+        Day {
+            day,
+            solve: solve_part::<S>,
+            solve_str: solve_part_str::<S>,
+        }
+    }
+}
+
+pub const DAYS: [Day; 11] = [
+    Day::new::<day01::Day01>(1),
+    Day::new::<day02::Day02>(2),
+    Day::new::<day03::Day03>(3),
+    Day::new::<day04::Day04>(4),
+    Day::new::<day05::Day05>(5),
+    Day::new::<day06::Day06>(6),
+    Day::new::<day07::Day07>(7),
+    Day::new::<day08::Day08>(8),
+    Day::new::<day09::Day09>(9),
+    Day::new::<day10::Day10>(10),
+    Day::new::<day11::Day11>(11),
+];
+
+type PartSolver<S> = fn(&<S as Solution>::Input) -> Result<<S as Solution>::Answer>;
+
+fn part_solver<S: Solution>(part: u8) -> Result<PartSolver<S>> {
+    match part {
+        1 => Ok(S::part1),
+        2 => Ok(S::part2),
+        _ => Err(Error::NoSolution(format!("there is no part {}", part))),
+    }
+}
+
+fn solve_part<S: Solution>(input_file: &str, part: u8) -> Result<String> {
+    let solve = part_solver::<S>(part)?;
+    let input = debug_span!("parse", input_file).in_scope(|| S::parse_file(input_file))?;
+    let answer = debug_span!("part")
+        .in_scope(|| solve(&input))
+        .map_err(|err| err.in_file(input_file))?;
+    info!(%answer);
+    Ok(answer.to_string())
+}
+
+fn solve_part_str<S: Solution>(input: &str, part: u8) -> Result<String> {
+    let solve = part_solver::<S>(part)?;
+    let input = debug_span!("parse").in_scope(|| S::parse_str(input))?;
+    let answer = debug_span!("part").in_scope(|| solve(&input))?;
+    info!(%answer);
+    Ok(answer.to_string())
+}
+
+pub fn find_day(day: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|entry| entry.day == day)
+}
+
+/// Path of the puzzle input for a day, relative to the crate root.
+pub fn default_input_file(day: u8) -> String {
+    format!("input/day{:02}.txt", day)
+}
+
+/// Runs one part of a day against an input file.
+pub fn solve(day: u8, part: u8, input_file: &str) -> Result<String> {
+    let _span = info_span!("solve", day, part).entered();
+    match find_day(day) {
+        Some(entry) => (entry.solve)(input_file, part),
+        None => Err(no_day(day)),
+    }
+}
+
+/// Runs one part of a day against puzzle input that is already in memory.
+pub fn solve_str(day: u8, part: u8, input: &str) -> Result<String> {
+    let _span = info_span!("solve", day, part).entered();
+    match find_day(day) {
+        Some(entry) => (entry.solve_str)(input, part),
+        None => Err(no_day(day)),
+    }
+}
+
+fn no_day(day: u8) -> Error {
+    Error::NoSolution(format!("day {} is not solved yet", day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_registered_in_order() {
+        let days: Vec<u8> = DAYS.iter().map(|entry| entry.day).collect();
+        assert_eq!((1..=11).collect::<Vec<u8>>(), days);
+    }
+
+    #[test]
+    fn test_solve_test01() {
+        assert_eq!("6440", solve(7, 1, "input/day07_test01.txt").unwrap());
+        assert_eq!("5905", solve(7, 2, "input/day07_test01.txt").unwrap());
+    }
+
+    #[test]
+    fn test_solve_str() {
+        let input = "32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483\n";
+        assert_eq!("6440", solve_str(7, 1, input).unwrap());
+        assert_eq!("5905", solve_str(7, 2, input).unwrap());
+    }
+
+    #[test]
+    fn test_parse_reader() {
+        let input = "32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483\n";
+        let hands = day07::Day07::parse_reader(input.as_bytes()).unwrap();
+        assert_eq!(6440, day07::Day07::part1(&hands).unwrap());
+    }
+
+    #[test]
+    fn test_solve_unknown() {
+        assert!(matches!(
+            solve(12, 1, "input/day07_test01.txt"),
+            Err(Error::NoSolution(_))
+        ));
+        assert!(matches!(
+            solve(7, 3, "input/day07_test01.txt"),
+            Err(Error::NoSolution(_))
+        ));
+    }
+
+    #[test]
+    fn test_solve_missing_input() {
+        assert!(matches!(
+            solve(7, 1, "input/day07_missing.txt"),
+            Err(Error::Io { .. })
+        ));
+    }
+}
@@ -0,0 +1,530 @@
+// https://adventofcode.com/about#faq_automation
+
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use regex::Regex;
+
+use super::fetch::{agent, DEFAULT_BASE_URL};
+use super::utils::{get_lines, parse_num, pieces};
+use super::{Error, Result};
+
+/// What the site said about a submitted answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+    Wait(Duration),
+    AlreadySolved,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::TooHigh => write!(f, "too high"),
+            Verdict::TooLow => write!(f, "too low"),
+            Verdict::Wrong => write!(f, "wrong"),
+            Verdict::Wait(wait) => write!(f, "wait {}s", wait.as_secs()),
+            Verdict::AlreadySolved => write!(f, "already solved"),
+        }
+    }
+}
+
+impl Verdict {
+    fn to_record(self) -> String {
+        match self {
+            Verdict::Correct => "correct".to_string(),
+            Verdict::TooHigh => "too_high".to_string(),
+            Verdict::TooLow => "too_low".to_string(),
+            Verdict::Wrong => "wrong".to_string(),
+            Verdict::Wait(wait) => format!("wait_{}", wait.as_secs()),
+            Verdict::AlreadySolved => "already_solved".to_string(),
+        }
+    }
+
+    fn from_record(record: &str) -> Option<Verdict> {
+        match record {
+            "correct" => Some(Verdict::Correct),
+            "too_high" => Some(Verdict::TooHigh),
+            "too_low" => Some(Verdict::TooLow),
+            "wrong" => Some(Verdict::Wrong),
+            "already_solved" => Some(Verdict::AlreadySolved),
+            _ => record
+                .strip_prefix("wait_")
+                .and_then(|secs| secs.parse().ok())
+                .map(|secs| Verdict::Wait(Duration::from_secs(secs))),
+        }
+    }
+}
+
+/// One line of the answer history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    pub verdict: Verdict,
+    /// Seconds since the Unix epoch
+    pub submitted_at: u64,
+}
+
+/// The verdict for a submission, and whether the site was actually asked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Submission {
+    pub verdict: Verdict,
+    pub posted: bool,
+}
+
+/// Posts answers to the site, keeping every submission in a history file.
+///
+/// Answers the history already rules out are answered from it instead of being posted again.
+pub struct Submitter {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+    history_file: PathBuf,
+}
+
+impl Submitter {
+    pub fn new(session: &str) -> Submitter {
+        Submitter {
+            agent: agent(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            session: session.trim().to_string(),
+            history_file: PathBuf::from("input/answers.txt"),
+        }
+    }
+
+    pub fn base_url(mut self, base_url: &str) -> Submitter {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn history_file(mut self, history_file: impl Into<PathBuf>) -> Submitter {
+        self.history_file = history_file.into();
+        self
+    }
+
+    pub fn answer_url(&self, day: u8) -> String {
+        format!("{}/2023/day/{}/answer", self.base_url, day)
+    }
+
+    /// Every recorded submission, oldest first.
+    pub fn history(&self) -> Result<Vec<Record>> {
+        if !self.history_file.exists() {
+            return Ok(vec![]);
+        }
+        let history_file = self.history_file.display().to_string();
+        let lines = get_lines(&history_file)?;
+        parse_history(lines).map_err(|err| err.in_file(&history_file))
+    }
+
+    pub fn submit(&self, day: u8, part: u8, answer: &str) -> Result<Submission> {
+        if !(1..=2).contains(&part) {
+            return Err(Error::NoSolution(format!("there is no part {}", part)));
+        }
+        let answer = answer.trim();
+        let now = unix_time();
+
+        let history = self.history()?;
+        if let Some(verdict) = known_verdict(&history, day, part, answer, now) {
+            return Ok(Submission {
+                verdict,
+                posted: false,
+            });
+        }
+
+        let verdict = self.post(day, part, answer)?;
+        // The site also says this for a level that isn't unlocked yet, so it rules nothing out
+        if verdict != Verdict::AlreadySolved {
+            self.record(&Record {
+                day,
+                part,
+                answer: answer.to_string(),
+                verdict,
+                submitted_at: now,
+            })?;
+        }
+        Ok(Submission {
+            verdict,
+            posted: true,
+        })
+    }
+
+    fn post(&self, day: u8, part: u8, answer: &str) -> Result<Verdict> {
+        let url = self.answer_url(day);
+        let http_error = |message: String| Error::Http {
+            url: url.clone(),
+            message,
+        };
+
+        let response = self
+            .agent
+            .post(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .send_form(&[("level", &part.to_string()), ("answer", answer)])
+            .map_err(|err| match err {
+                ureq::Error::Status(status, response) => http_error(format!(
+                    "server returned {} {}",
+                    status,
+                    response.status_text()
+                )),
+                ureq::Error::Transport(transport) => http_error(transport.to_string()),
+            })?;
+
+        let body = response
+            .into_string()
+            .map_err(|err| http_error(err.to_string()))?;
+        parse_verdict(&body).ok_or_else(|| http_error("unrecognised response".to_string()))
+    }
+
+    fn record(&self, record: &Record) -> Result<()> {
+        let write_error = |source| Error::Write {
+            path: self.history_file.display().to_string(),
+            source,
+        };
+
+        if let Some(history_dir) = self.history_file.parent() {
+            fs::create_dir_all(history_dir).map_err(write_error)?;
+        }
+        let mut history_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.history_file)
+            .map_err(write_error)?;
+        writeln!(
+            history_file,
+            "{}\t{}\t{}\t{}\t{}",
+            record.day,
+            record.part,
+            record.answer,
+            record.verdict.to_record(),
+            record.submitted_at
+        )
+        .map_err(write_error)
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs())
+}
+
+fn parse_history(lines: Vec<String>) -> Result<Vec<Record>> {
+    let mut history: Vec<Record> = vec![];
+    for (line_index, line) in lines.iter().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<(usize, &str)> = pieces(line, &['\t']).collect();
+        let [day, part, (_, answer), (_, verdict), submitted_at] = fields[..] else {
+            return Err(Error::parse(
+                line_index + 1,
+                1,
+                "expected `<day>\\t<part>\\t<answer>\\t<verdict>\\t<time>`",
+            ));
+        };
+        let Some(verdict) = Verdict::from_record(verdict) else {
+            return Err(Error::parse(
+                line_index + 1,
+                1,
+                format!("invalid verdict `{}`", verdict),
+            ));
+        };
+        history.push(Record {
+            day: parse_num(line_index, line, day)?,
+            part: parse_num(line_index, line, part)?,
+            answer: answer.to_string(),
+            verdict,
+            submitted_at: parse_num(line_index, line, submitted_at)?,
+        });
+    }
+    Ok(history)
+}
+
+/// The verdict the history already gives `answer`, so it need not be posted.
+fn known_verdict(history: &[Record], day: u8, part: u8, answer: &str, now: u64) -> Option<Verdict> {
+    let records: Vec<&Record> = history
+        .iter()
+        .filter(|record| record.day == day && record.part == part)
+        .collect();
+
+    // Still waiting out the last submission's timeout
+    if let Some(record) = records.last() {
+        if let Verdict::Wait(wait) = record.verdict {
+            let ready_at = record.submitted_at + wait.as_secs();
+            if now < ready_at {
+                return Some(Verdict::Wait(Duration::from_secs(ready_at - now)));
+            }
+        }
+    }
+
+    for record in &records {
+        match record.verdict {
+            Verdict::Correct if record.answer == answer => return Some(Verdict::Correct),
+            Verdict::Correct => return Some(Verdict::AlreadySolved),
+            Verdict::TooHigh | Verdict::TooLow | Verdict::Wrong if record.answer == answer => {
+                return Some(record.verdict)
+            }
+            _ => (),
+        }
+    }
+
+    // Answers outside the bounds set by earlier too high / too low verdicts
+    let answer: i128 = answer.parse().ok()?;
+    for record in &records {
+        let Ok(bound) = record.answer.parse::<i128>() else {
+            continue;
+        };
+        match record.verdict {
+            Verdict::TooHigh if answer >= bound => return Some(Verdict::TooHigh),
+            Verdict::TooLow if answer <= bound => return Some(Verdict::TooLow),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Reads the verdict out of the page the site returns for a submission.
+fn parse_verdict(body: &str) -> Option<Verdict> {
+    lazy_static! {
+        static ref RE_WAIT: Regex =
+            Regex::new(r"(?:(?P<min>\d+)m\s*)?(?:(?P<sec>\d+)s\s*)?left to wait").unwrap();
+    }
+
+    if body.contains("That's the right answer") {
+        Some(Verdict::Correct)
+    } else if body.contains("answer is too high") {
+        Some(Verdict::TooHigh)
+    } else if body.contains("answer is too low") {
+        Some(Verdict::TooLow)
+    } else if body.contains("That's not the right answer") {
+        Some(Verdict::Wrong)
+    } else if body.contains("You gave an answer too recently") {
+        let caps = RE_WAIT.captures(body)?;
+        let part_secs = |name: &str| {
+            caps.name(name)
+                .map_or(0, |secs| secs.as_str().parse().unwrap_or(0))
+        };
+        let secs = part_secs("min") * 60 + part_secs("sec");
+        Some(Verdict::Wait(Duration::from_secs(secs)))
+    } else if body.contains("Did you already complete it") {
+        Some(Verdict::AlreadySolved)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use crate::aoc::testing::{stub_server, temp_path, Request};
+
+    fn page(message: &str) -> String {
+        format!(
+            "<html><body><main><article><p>{}</p></article></main></body></html>",
+            message
+        )
+    }
+
+    /// Answers each request with the next page.
+    fn mock_site(pages: Vec<String>) -> (String, Arc<Mutex<Vec<Request>>>) {
+        stub_server(pages.into_iter().map(|page| ("200 OK", page)).collect())
+    }
+
+    #[test]
+    fn test_parse_verdict() {
+        assert_eq!(
+            Some(Verdict::Correct),
+            parse_verdict(&page(
+                "That's the right answer! You are one gold star closer."
+            ))
+        );
+        assert_eq!(
+            Some(Verdict::TooHigh),
+            parse_verdict(&page(
+                "That's not the right answer; your answer is too high."
+            ))
+        );
+        assert_eq!(
+            Some(Verdict::TooLow),
+            parse_verdict(&page(
+                "That's not the right answer; your answer is too low."
+            ))
+        );
+        assert_eq!(
+            Some(Verdict::Wrong),
+            parse_verdict(&page(
+                "That's not the right answer. Please wait one minute."
+            ))
+        );
+        assert_eq!(
+            Some(Verdict::Wait(Duration::from_secs(83))),
+            parse_verdict(&page(
+                "You gave an answer too recently. You have 1m 23s left to wait."
+            ))
+        );
+        assert_eq!(
+            Some(Verdict::Wait(Duration::from_secs(37))),
+            parse_verdict(&page(
+                "You gave an answer too recently. You have 37s left to wait."
+            ))
+        );
+        assert_eq!(
+            Some(Verdict::AlreadySolved),
+            parse_verdict(&page(
+                "You don't seem to be solving the right level.  Did you already complete it?"
+            ))
+        );
+        assert_eq!(None, parse_verdict(&page("Something else entirely")));
+    }
+
+    #[test]
+    fn test_known_verdict() {
+        let record = |answer: &str, verdict| Record {
+            day: 7,
+            part: 1,
+            answer: answer.to_string(),
+            verdict,
+            submitted_at: 100,
+        };
+        let history = vec![
+            record("500", Verdict::TooHigh),
+            record("100", Verdict::TooLow),
+            record("abc", Verdict::Wrong),
+        ];
+        assert_eq!(
+            Some(Verdict::TooHigh),
+            known_verdict(&history, 7, 1, "500", 200)
+        );
+        assert_eq!(
+            Some(Verdict::TooHigh),
+            known_verdict(&history, 7, 1, "501", 200)
+        );
+        assert_eq!(
+            Some(Verdict::TooLow),
+            known_verdict(&history, 7, 1, "99", 200)
+        );
+        assert_eq!(
+            Some(Verdict::Wrong),
+            known_verdict(&history, 7, 1, "abc", 200)
+        );
+        assert_eq!(None, known_verdict(&history, 7, 1, "250", 200));
+        assert_eq!(None, known_verdict(&history, 7, 2, "501", 200));
+
+        let waiting = vec![record("250", Verdict::Wait(Duration::from_secs(60)))];
+        assert_eq!(
+            Some(Verdict::Wait(Duration::from_secs(40))),
+            known_verdict(&waiting, 7, 1, "250", 120)
+        );
+        assert_eq!(None, known_verdict(&waiting, 7, 1, "250", 160));
+
+        let solved = vec![record("6440", Verdict::Correct)];
+        assert_eq!(
+            Some(Verdict::Correct),
+            known_verdict(&solved, 7, 1, "6440", 200)
+        );
+        assert_eq!(
+            Some(Verdict::AlreadySolved),
+            known_verdict(&solved, 7, 1, "6441", 200)
+        );
+
+        // Histories written before such replies stopped being recorded don't block anything
+        let locked = vec![record("6440", Verdict::AlreadySolved)];
+        assert_eq!(None, known_verdict(&locked, 7, 1, "6440", 200));
+    }
+
+    #[test]
+    fn test_submit_locked_level() {
+        let (base_url, requests) = mock_site(vec![
+            page("You don't seem to be solving the right level.  Did you already complete it?"),
+            page("That's the right answer!"),
+        ]);
+        let history_file = temp_path("submit-locked.txt");
+        let submitter = Submitter::new("secret")
+            .base_url(&base_url)
+            .history_file(&history_file);
+
+        let submission = submitter.submit(7, 2, "5905").unwrap();
+        assert_eq!(Verdict::AlreadySolved, submission.verdict);
+        assert!(submitter.history().unwrap().is_empty());
+
+        // Once part 1 is solved, part 2 can still be posted
+        let submission = submitter.submit(7, 2, "5905").unwrap();
+        assert_eq!(Verdict::Correct, submission.verdict);
+        assert!(submission.posted);
+        assert_eq!(2, requests.lock().unwrap().len());
+
+        fs::remove_file(history_file).unwrap();
+    }
+
+    #[test]
+    fn test_submit_records_history() {
+        let (base_url, requests) = mock_site(vec![
+            page("That's not the right answer; your answer is too high."),
+            page("That's the right answer!"),
+        ]);
+        let history_file = temp_path("submit-records.txt");
+        let submitter = Submitter::new("secret")
+            .base_url(&base_url)
+            .history_file(&history_file);
+
+        let submission = submitter.submit(7, 1, "7000").unwrap();
+        assert_eq!(Verdict::TooHigh, submission.verdict);
+        assert!(submission.posted);
+
+        // Ruled out by the history, so neither reaches the site
+        assert!(!submitter.submit(7, 1, "7000").unwrap().posted);
+        assert_eq!(
+            Verdict::TooHigh,
+            submitter.submit(7, 1, "8000").unwrap().verdict
+        );
+
+        let submission = submitter.submit(7, 1, "6440").unwrap();
+        assert_eq!(Verdict::Correct, submission.verdict);
+        assert!(submission.posted);
+
+        assert_eq!(
+            vec!["level=1&answer=7000", "level=1&answer=6440"],
+            requests
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|request| request.body.as_str())
+                .collect::<Vec<_>>()
+        );
+        let history: Vec<(String, Verdict)> = submitter
+            .history()
+            .unwrap()
+            .into_iter()
+            .map(|record| (record.answer, record.verdict))
+            .collect();
+        assert_eq!(
+            vec![
+                ("7000".to_string(), Verdict::TooHigh),
+                ("6440".to_string(), Verdict::Correct)
+            ],
+            history
+        );
+
+        fs::remove_file(history_file).unwrap();
+    }
+
+    #[test]
+    fn test_parse_history_invalid_verdict() {
+        let err = parse_history(vec!["7\t1\t6440\tmaybe\t100".to_string()]).unwrap_err();
+        assert_eq!("<input>:1:1: invalid verdict `maybe`", err.to_string());
+    }
+}
@@ -1,27 +1,152 @@
 use std::{
-    error::Error,
     fs::File,
     io::{BufRead, BufReader},
-    path::Path,
+    str::FromStr,
 };
 
-pub fn get_lines(input_file: &str) -> Vec<String> {
-    let path = Path::new(input_file);
-    let display = path.display();
-
-    let file = match File::open(input_file) {
-        // The `description` method of `io::Error` returns a string that describes the error
-        Err(why) => panic!(
-            "couldn't open {}: {}",
-            display,
-            <dyn Error>::to_string(&why)
-        ),
-        Ok(file) => file,
+use ::grid::Grid;
+
+use super::error::{Error, Result};
+
+pub mod grid;
+
+pub fn get_lines(input_file: &str) -> Result<Vec<String>> {
+    let io_error = |source| Error::Io {
+        path: input_file.to_string(),
+        source,
     };
 
-    let reader = BufReader::new(file);
+    let file = File::open(input_file).map_err(io_error)?;
+
+    read_lines(BufReader::new(file), input_file)
+}
+
+/// Reads every line from `reader`, naming it `source` if reading fails.
+pub fn read_lines<R: BufRead>(reader: R, source: &str) -> Result<Vec<String>> {
     reader
         .lines()
-        .map(|l| l.expect("Could not parse line"))
-        .collect()
+        .collect::<std::io::Result<Vec<String>>>()
+        .map_err(|err| Error::Io {
+            path: source.to_string(),
+            source: err,
+        })
+}
+
+/// 1-based column of the byte `offset` into `line`.
+pub fn column(line: &str, offset: usize) -> usize {
+    line.char_indices()
+        .take_while(|&(index, _)| index < offset)
+        .count()
+        + 1
+}
+
+/// The whitespace-separated words of `text`, each with its byte offset into `text`.
+pub fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut rest = (0, text);
+    std::iter::from_fn(move || {
+        let (offset, text) = rest;
+        let start = text.find(|c: char| !c.is_whitespace())?;
+        let end = text[start..]
+            .find(char::is_whitespace)
+            .map_or(text.len(), |len| start + len);
+        rest = (offset + end, &text[end..]);
+        Some((offset + start, &text[start..end]))
+    })
+}
+
+/// The pieces of `text` between any of `separators`, each with its byte offset into `text`.
+pub fn pieces<'a>(
+    text: &'a str,
+    separators: &'a [char],
+) -> impl Iterator<Item = (usize, &'a str)> + 'a {
+    text.split(separators).scan(0, move |offset, piece| {
+        let start = *offset;
+        let end = start + piece.len();
+        *offset = end + text[end..].chars().next().map_or(0, char::len_utf8);
+        Some((start, piece))
+    })
+}
+
+/// Parses `token`, found at byte `offset` into the 0-based `line_index`th line, reporting where it
+/// failed.
+pub fn parse_num<T: FromStr>(
+    line_index: usize,
+    line: &str,
+    (offset, token): (usize, &str),
+) -> Result<T> {
+    token.parse().map_err(|_| {
+        Error::parse(
+            line_index + 1,
+            column(line, offset),
+            format!("invalid number `{}`", token),
+        )
+    })
+}
+
+/// Appends the row parsed from the 0-based `line_index`th line, rejecting empty or ragged rows.
+pub fn push_grid_row<T>(grid: &mut Grid<T>, line_index: usize, row: Vec<T>) -> Result<()> {
+    if row.is_empty() {
+        return Err(Error::parse(line_index + 1, 1, "empty row"));
+    }
+    if grid.rows() > 0 && row.len() != grid.cols() {
+        return Err(Error::parse(
+            line_index + 1,
+            row.len().min(grid.cols()) + 1,
+            format!("expected {} columns, found {}", grid.cols(), row.len()),
+        ));
+    }
+    grid.push_row(row);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_lines_missing_file() {
+        let err = get_lines("input/missing.txt").unwrap_err();
+        assert!(matches!(err, Error::Io { ref path, .. } if path == "input/missing.txt"));
+    }
+
+    #[test]
+    fn test_column() {
+        let line = "Game 12: 3 blue";
+        assert_eq!(6, column(line, 5));
+        assert_eq!(3, column("é 12", 3));
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            vec![(2, "79"), (6, "x4")],
+            words("  79\t x4").collect::<Vec<_>>()
+        );
+        assert_eq!(0, words("   ").count());
+    }
+
+    #[test]
+    fn test_pieces() {
+        assert_eq!(
+            vec![(0, "Card 1"), (7, " 41 "), (12, " 83")],
+            pieces("Card 1: 41 | 83", &[':', '|']).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_push_grid_row_ragged() {
+        let mut grid: Grid<u8> = Grid::new(0, 0);
+        push_grid_row(&mut grid, 0, vec![1, 2, 3]).unwrap();
+        let err = push_grid_row(&mut grid, 1, vec![4, 5]).unwrap_err();
+        assert_eq!("<input>:2:3: expected 3 columns, found 2", err.to_string());
+        assert_eq!(1, grid.rows());
+    }
+
+    #[test]
+    fn test_parse_num() {
+        let line = "seeds: 79 x4";
+        assert_eq!(79u64, parse_num(0, line, (7, "79")).unwrap());
+        let err = parse_num::<u64>(1, line, (10, "x4")).unwrap_err();
+        assert_eq!("<input>:2:11: invalid number `x4`", err.to_string());
+    }
 }
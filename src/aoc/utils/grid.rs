@@ -0,0 +1,239 @@
+use std::ops::Range;
+
+use grid::Grid;
+
+use super::push_grid_row;
+use crate::aoc::Result;
+
+/// A (row, col) position within a grid.
+pub type Pos = (usize, usize);
+
+/// A signed (row, col) step between positions.
+pub type Offset = (isize, isize);
+
+pub const NORTH: Offset = (-1, 0);
+pub const SOUTH: Offset = (1, 0);
+pub const EAST: Offset = (0, 1);
+pub const WEST: Offset = (0, -1);
+
+/// The orthogonal steps, in reading order.
+pub const OFFSETS_4: [Offset; 4] = [NORTH, WEST, EAST, SOUTH];
+
+/// The orthogonal and diagonal steps, in reading order.
+pub const OFFSETS_8: [Offset; 8] = [(-1, -1), NORTH, (-1, 1), WEST, EAST, (1, -1), SOUTH, (1, 1)];
+
+/// A run of consecutive cells within one row or column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// The row of a row span, or the column of a column span
+    pub index: usize,
+    pub range: Range<usize>,
+}
+
+/// The in-bounds positions adjacent to a cell, from `GridExt::neighbours_4` or `neighbours_8`.
+pub struct Neighbours<'a, T> {
+    grid: &'a Grid<T>,
+    pos: Pos,
+    offsets: std::slice::Iter<'static, Offset>,
+}
+
+impl<T> Iterator for Neighbours<'_, T> {
+    type Item = Pos;
+
+    fn next(&mut self) -> Option<Pos> {
+        let (grid, pos) = (self.grid, self.pos);
+        self.offsets.find_map(|&offset| grid.offset(pos, offset))
+    }
+}
+
+/// Bounds-checked movement and neighbourhoods on top of `grid::Grid`.
+pub trait GridExt<T> {
+    /// `pos` moved by `offset`, if that stays inside the grid.
+    fn offset(&self, pos: Pos, offset: Offset) -> Option<Pos>;
+
+    /// The in-bounds positions orthogonally adjacent to `pos`, in reading order.
+    fn neighbours_4(&self, pos: Pos) -> Neighbours<'_, T>;
+
+    /// The in-bounds positions orthogonally or diagonally adjacent to `pos`, in reading order.
+    fn neighbours_8(&self, pos: Pos) -> Neighbours<'_, T>;
+
+    /// Maximal runs of cells within each row that satisfy `pred`, top to bottom.
+    fn row_spans(&self, pred: impl Fn(&T) -> bool) -> Vec<Span>;
+
+    /// Maximal runs of cells within each column that satisfy `pred`, left to right.
+    fn col_spans(&self, pred: impl Fn(&T) -> bool) -> Vec<Span>;
+}
+
+impl<T> GridExt<T> for Grid<T> {
+    fn offset(&self, (row, col): Pos, (row_offset, col_offset): Offset) -> Option<Pos> {
+        let row = row.checked_add_signed(row_offset)?;
+        let col = col.checked_add_signed(col_offset)?;
+        (row < self.rows() && col < self.cols()).then_some((row, col))
+    }
+
+    fn neighbours_4(&self, pos: Pos) -> Neighbours<'_, T> {
+        Neighbours {
+            grid: self,
+            pos,
+            offsets: OFFSETS_4.iter(),
+        }
+    }
+
+    fn neighbours_8(&self, pos: Pos) -> Neighbours<'_, T> {
+        Neighbours {
+            grid: self,
+            pos,
+            offsets: OFFSETS_8.iter(),
+        }
+    }
+
+    fn row_spans(&self, pred: impl Fn(&T) -> bool) -> Vec<Span> {
+        let mut spans = vec![];
+        for (row, cells) in self.iter_rows().enumerate() {
+            spans.extend(spans_of(row, cells.map(&pred)));
+        }
+        spans
+    }
+
+    fn col_spans(&self, pred: impl Fn(&T) -> bool) -> Vec<Span> {
+        let mut spans = vec![];
+        for (col, cells) in self.iter_cols().enumerate() {
+            spans.extend(spans_of(col, cells.map(&pred)));
+        }
+        spans
+    }
+}
+
+fn spans_of(index: usize, matches: impl Iterator<Item = bool>) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut start: Option<usize> = None;
+    let mut len = 0;
+    for (i, is_match) in matches.enumerate() {
+        match (is_match, start) {
+            (true, None) => start = Some(i),
+            (false, Some(span_start)) => {
+                spans.push(Span {
+                    index,
+                    range: span_start..i,
+                });
+                start = None;
+            }
+            _ => (),
+        }
+        len = i + 1;
+    }
+    if let Some(span_start) = start {
+        spans.push(Span {
+            index,
+            range: span_start..len,
+        });
+    }
+    spans
+}
+
+/// Builds a grid with one row per line, parsing each character with its 0-based line and column.
+///
+/// Rows must all be the same, non-zero, length.
+pub fn parse_grid<T: Default>(
+    lines: &[String],
+    mut parse_cell: impl FnMut(usize, usize, char) -> Result<T>,
+) -> Result<Grid<T>> {
+    let mut grid = Grid::new(0, 0);
+    for (line_index, line) in lines.iter().enumerate() {
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(col, c)| parse_cell(line_index, col, c))
+            .collect::<Result<Vec<T>>>()?;
+        push_grid_row(&mut grid, line_index, row)?;
+    }
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::aoc::Error;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_neighbours_at_corner() {
+        let grid: Grid<u8> = Grid::new(3, 4);
+        assert_eq!(
+            vec![(0, 1), (1, 0)],
+            grid.neighbours_4((0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(1, 2), (1, 3), (2, 2)],
+            grid.neighbours_8((2, 3)).collect::<Vec<_>>()
+        );
+        assert_eq!(8, grid.neighbours_8((1, 1)).count());
+    }
+
+    #[test]
+    fn test_offset() {
+        let grid: Grid<u8> = Grid::new(3, 4);
+        assert_eq!(Some((0, 3)), grid.offset((1, 3), NORTH));
+        assert_eq!(None, grid.offset((0, 3), NORTH));
+        assert_eq!(None, grid.offset((1, 3), EAST));
+        assert_eq!(Some((2, 0)), grid.offset((0, 2), (2, -2)));
+    }
+
+    #[test]
+    fn test_spans() {
+        let grid = parse_grid(&lines(&["12.3", "4..5"]), |_, _, c| Ok(c)).unwrap();
+        let digit = |c: &char| c.is_ascii_digit();
+        assert_eq!(
+            vec![
+                Span {
+                    index: 0,
+                    range: 0..2
+                },
+                Span {
+                    index: 0,
+                    range: 3..4
+                },
+                Span {
+                    index: 1,
+                    range: 0..1
+                },
+                Span {
+                    index: 1,
+                    range: 3..4
+                },
+            ],
+            grid.row_spans(digit)
+        );
+        assert_eq!(
+            vec![
+                Span {
+                    index: 0,
+                    range: 0..2
+                },
+                Span {
+                    index: 1,
+                    range: 0..1
+                },
+                Span {
+                    index: 3,
+                    range: 0..2
+                },
+            ],
+            grid.col_spans(digit)
+        );
+    }
+
+    #[test]
+    fn test_parse_grid_invalid_cell() {
+        let err = parse_grid(&lines(&["..", ".x"]), |line_index, col, c| match c {
+            '.' => Ok(()),
+            _ => Err(Error::parse(line_index + 1, col + 1, "invalid cell")),
+        })
+        .unwrap_err();
+        assert_eq!("<input>:2:2: invalid cell", err.to_string());
+    }
+}
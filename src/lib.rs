@@ -1,5 +1,11 @@
 #[macro_use]
 extern crate lazy_static;
 
-#[allow(dead_code)]
-mod aoc;
\ No newline at end of file
+mod aoc;
+
+pub use aoc::{
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11,
+    default_input_file, export, find_day, session_from_env, solve, solve_str, Day, Day01, Day02,
+    Day03, Day04, Day05, Day06, Day07, Day08, Day09, Day10, Day11, Error, Fetcher, Record, Result,
+    Solution, Submission, Submitter, Verdict, DAYS, SESSION_VAR,
+};
@@ -0,0 +1,387 @@
+use std::{
+    env,
+    io::{self, IsTerminal, Read},
+    process::ExitCode,
+};
+
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+use aoc2023::{
+    day03, day10, day11, default_input_file, export::Exporter, find_day, session_from_env, solve,
+    solve_str, Day03, Day10, Day11, Fetcher, Solution, Submitter, SESSION_VAR,
+};
+
+const USAGE: &str = "usage: aoc2023 [-v|-vv|--log <filter>] run <day> [<part>] [--input <path>|-]
+       aoc2023 fetch <day>
+       aoc2023 submit <day> <part> [<answer>] [--input <path>|-]
+       aoc2023 render 10 [--ascii] [--input <path>|-]
+       aoc2023 export <day> <path.svg|path.ppm> [--input <path>|-]";
+
+/// Environment variable holding a log filter, such as `aoc2023::aoc::day08=trace`.
+const LOG_VAR: &str = "AOC_LOG";
+
+/// Removes the logging options from `args`, returning the log filter they ask for.
+fn take_log_filter(args: &mut Vec<String>) -> Result<Option<String>, String> {
+    let mut log_filter = None;
+    let mut index = 0;
+    while index < args.len() {
+        match args[index].as_str() {
+            "-v" => log_filter = Some("aoc2023=debug".to_string()),
+            "-vv" => log_filter = Some("aoc2023=trace".to_string()),
+            "--log" => {
+                if index + 1 == args.len() {
+                    return Err("--log needs a filter".to_string());
+                }
+                log_filter = Some(args.remove(index + 1));
+            }
+            _ => {
+                index += 1;
+                continue;
+            }
+        }
+        args.remove(index);
+    }
+    Ok(log_filter)
+}
+
+/// Logs to stderr, with span timings, at the level asked for on the command line or in `AOC_LOG`.
+fn init_logging(log_filter: Option<String>) -> Result<(), String> {
+    let filter = match log_filter {
+        Some(log_filter) => EnvFilter::try_new(&log_filter)
+            .map_err(|err| format!("invalid log filter `{}`: {}", log_filter, err))?,
+        None => EnvFilter::try_from_env(LOG_VAR).unwrap_or_else(|_| EnvFilter::new("warn")),
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(io::stderr)
+        .init();
+    Ok(())
+}
+
+#[derive(Debug)]
+struct RunArgs {
+    day: u8,
+    parts: Vec<u8>,
+    input_file: Option<String>,
+}
+
+fn parse_run_args(args: &[String]) -> Result<RunArgs, String> {
+    let mut positional: Vec<&str> = vec![];
+    let mut input_file: Option<String> = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--input" | "-i" => match iter.next() {
+                Some(path) => input_file = Some(path.clone()),
+                None => return Err("--input needs a path".to_string()),
+            },
+            flag if flag.starts_with('-') => return Err(format!("unknown option: {}", flag)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let day: u8 = match positional.first() {
+        Some(day) => day.parse().map_err(|_| format!("invalid day: {}", day))?,
+        None => return Err("missing day".to_string()),
+    };
+    if find_day(day).is_none() {
+        return Err(format!("no solution for day {}", day));
+    }
+
+    let parts = match positional.get(1) {
+        Some(&"1") => vec![1],
+        Some(&"2") => vec![2],
+        Some(part) => return Err(format!("invalid part: {}", part)),
+        None => vec![1, 2],
+    };
+
+    if positional.len() > 2 {
+        return Err(format!("unexpected argument: {}", positional[2]));
+    }
+
+    Ok(RunArgs {
+        day,
+        parts,
+        input_file,
+    })
+}
+
+/// `--input -` reads the puzzle from stdin, once for every part.
+fn read_stdin_input(run_args: &RunArgs) -> Result<Option<String>, String> {
+    match run_args.input_file.as_deref() {
+        Some("-") => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|err| format!("couldn't read stdin: {}", err))?;
+            Ok(Some(input))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn solve_run_part(
+    run_args: &RunArgs,
+    part: u8,
+    stdin_input: &Option<String>,
+) -> Result<String, String> {
+    match (stdin_input, &run_args.input_file) {
+        (Some(input), _) => solve_str(run_args.day, part, input),
+        (None, Some(input_file)) => solve(run_args.day, part, input_file),
+        (None, None) => solve(run_args.day, part, &default_input_file(run_args.day)),
+    }
+    .map_err(|err| err.to_string())
+}
+
+fn run(run_args: RunArgs) -> Result<(), String> {
+    let stdin_input = read_stdin_input(&run_args)?;
+    for &part in &run_args.parts {
+        let answer = solve_run_part(&run_args, part, &stdin_input)?;
+        println!("Day {:02} part {}: {}", run_args.day, part, answer);
+    }
+    Ok(())
+}
+
+fn session() -> Result<String, String> {
+    session_from_env().ok_or(format!(
+        "set {} to your adventofcode.com session cookie",
+        SESSION_VAR
+    ))
+}
+
+/// Downloads a day's input into `input/`, with the session token from `AOC_SESSION`.
+fn fetch(args: &[String]) -> Result<(), String> {
+    let day: u8 = match args {
+        [day] => day.parse().map_err(|_| format!("invalid day: {}", day))?,
+        [] => return Err("missing day".to_string()),
+        [_, arg, ..] => return Err(format!("unexpected argument: {}", arg)),
+    };
+    let fetcher = Fetcher::new(&session()?);
+    let input_file = fetcher.fetch(day).map_err(|err| err.to_string())?;
+    println!("Day {:02} input: {}", day, input_file.display());
+    Ok(())
+}
+
+/// Like `run`, for a single part, with an optional answer after the part to submit instead.
+fn parse_submit_args(args: &[String]) -> Result<(RunArgs, Option<String>), String> {
+    let mut args = args.to_vec();
+    let answer = match (args.get(1), args.get(2)) {
+        (Some(part), Some(answer)) if !part.starts_with('-') && !answer.starts_with('-') => {
+            Some(args.remove(2))
+        }
+        _ => None,
+    };
+    let run_args = parse_run_args(&args)?;
+    if run_args.parts.len() != 1 {
+        return Err("missing part".to_string());
+    }
+    Ok((run_args, answer))
+}
+
+/// Submits an answer, solving the part first unless one is given.
+fn submit((run_args, answer): (RunArgs, Option<String>)) -> Result<(), String> {
+    let part = run_args.parts[0];
+    let answer = match answer {
+        Some(answer) => answer,
+        None => solve_run_part(&run_args, part, &read_stdin_input(&run_args)?)?,
+    };
+
+    let submitter = Submitter::new(&session()?);
+    let submission = submitter
+        .submit(run_args.day, part, &answer)
+        .map_err(|err| err.to_string())?;
+    println!(
+        "Day {:02} part {}: {} is {}{}",
+        run_args.day,
+        part,
+        answer,
+        submission.verdict,
+        if submission.posted {
+            ""
+        } else {
+            " (from history, not submitted)"
+        }
+    );
+    Ok(())
+}
+
+/// Parses a day's input, from `--input` if given.
+fn read_input<S: Solution>(run_args: &RunArgs) -> Result<S::Input, String> {
+    match (read_stdin_input(run_args)?, &run_args.input_file) {
+        (Some(input), _) => S::parse_str(&input),
+        (None, Some(input_file)) => S::parse_file(input_file),
+        (None, None) => S::parse_file(&default_input_file(run_args.day)),
+    }
+    .map_err(|err| err.to_string())
+}
+
+/// Like `run`, without a part.
+fn parse_day_args(args: &[String]) -> Result<RunArgs, String> {
+    let run_args = parse_run_args(args)?;
+    if run_args.parts.len() != 2 {
+        return Err(format!("unexpected argument: {}", run_args.parts[0]));
+    }
+    Ok(run_args)
+}
+
+/// Draws a day's input, box drawing in colour on a terminal unless `--ascii` asks for plain text.
+fn render(args: &[String]) -> Result<(), String> {
+    let ascii = args.iter().any(|arg| arg == "--ascii");
+    let args: Vec<String> = args
+        .iter()
+        .filter(|&arg| arg != "--ascii")
+        .cloned()
+        .collect();
+    let run_args = parse_day_args(&args)?;
+    if run_args.day != 10 {
+        return Err(format!("no renderer for day {}", run_args.day));
+    }
+
+    let input = read_input::<Day10>(&run_args)?;
+    let style = if ascii {
+        day10::RenderStyle::ASCII
+    } else {
+        day10::RenderStyle {
+            box_drawing: true,
+            colour: io::stdout().is_terminal(),
+        }
+    };
+    let rendered = day10::render(&input, style).map_err(|err| err.to_string())?;
+    print!("{}", rendered);
+    Ok(())
+}
+
+/// Writes a picture of a day's input, as SVG or PPM depending on the path's extension.
+fn export(args: &[String]) -> Result<(), String> {
+    let mut args = args.to_vec();
+    let path = match args.get(1) {
+        Some(path) if !path.starts_with('-') => args.remove(1),
+        _ => return Err("missing picture path".to_string()),
+    };
+    let run_args = parse_day_args(&args)?;
+
+    let exporter = Exporter::new();
+    match run_args.day {
+        3 => {
+            let input = read_input::<Day03>(&run_args)?;
+            exporter.write(&input.engine_schematic, &path, day03::cell_style)
+        }
+        10 => {
+            let input = read_input::<Day10>(&run_args)?;
+            let tiles = day10::classify_tiles(&input).map_err(|err| err.to_string())?;
+            exporter.write(&tiles, &path, day10::cell_style)
+        }
+        11 => {
+            let input = read_input::<Day11>(&run_args)?;
+            exporter.write(&input.image, &path, day11::cell_style)
+        }
+        day => return Err(format!("no picture for day {}", day)),
+    }
+    .map_err(|err| err.to_string())?;
+    println!("Day {:02} picture: {}", run_args.day, path);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    if let Err(err) = take_log_filter(&mut args).and_then(init_logging) {
+        return fail(&err);
+    }
+
+    let result = match args.first().map(String::as_str) {
+        Some("run") => parse_run_args(&args[1..]).and_then(run),
+        Some("fetch") => fetch(&args[1..]),
+        Some("submit") => parse_submit_args(&args[1..]).and_then(submit),
+        Some("render") => render(&args[1..]),
+        Some("export") => export(&args[1..]),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Some(command) => Err(format!("unknown command: {}", command)),
+        None => Err("missing command".to_string()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => fail(&err),
+    }
+}
+
+fn fail(err: &str) -> ExitCode {
+    eprintln!("error: {}\n{}", err, USAGE);
+    ExitCode::FAILURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_run_args_both_parts() {
+        let run_args = parse_run_args(&args(&["5"])).unwrap();
+        assert_eq!(5, run_args.day);
+        assert_eq!(vec![1, 2], run_args.parts);
+        assert_eq!(None, run_args.input_file);
+    }
+
+    #[test]
+    fn test_parse_run_args_part_and_input() {
+        let run_args =
+            parse_run_args(&args(&["7", "2", "--input", "input/day07_test01.txt"])).unwrap();
+        assert_eq!(7, run_args.day);
+        assert_eq!(vec![2], run_args.parts);
+        assert_eq!(
+            Some("input/day07_test01.txt".to_string()),
+            run_args.input_file
+        );
+    }
+
+    #[test]
+    fn test_parse_run_args_invalid() {
+        assert!(parse_run_args(&args(&[])).is_err());
+        assert!(parse_run_args(&args(&["12"])).is_err());
+        assert!(parse_run_args(&args(&["3", "3"])).is_err());
+        assert!(parse_run_args(&args(&["3", "--input"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_submit_args() {
+        let (run_args, answer) = parse_submit_args(&args(&["7", "1", "6440"])).unwrap();
+        assert_eq!((7, vec![1]), (run_args.day, run_args.parts));
+        assert_eq!(Some("6440".to_string()), answer);
+
+        let (run_args, answer) =
+            parse_submit_args(&args(&["7", "2", "--input", "input/day07_test01.txt"])).unwrap();
+        assert_eq!(vec![2], run_args.parts);
+        assert_eq!(None, answer);
+
+        assert!(parse_submit_args(&args(&["7"])).is_err());
+    }
+
+    #[test]
+    fn test_take_log_filter() {
+        let mut run_args = args(&["-v", "run", "8", "--log", "aoc2023::aoc::day08=trace"]);
+        assert_eq!(
+            Some("aoc2023::aoc::day08=trace".to_string()),
+            take_log_filter(&mut run_args).unwrap()
+        );
+        assert_eq!(args(&["run", "8"]), run_args);
+
+        let mut run_args = args(&["run", "8", "-vv"]);
+        assert_eq!(
+            Some("aoc2023=trace".to_string()),
+            take_log_filter(&mut run_args).unwrap()
+        );
+        assert_eq!(args(&["run", "8"]), run_args);
+
+        assert!(take_log_filter(&mut args(&["run", "8", "--log"])).is_err());
+    }
+}
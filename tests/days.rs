@@ -0,0 +1,135 @@
+// Exercises each day through the public `aoc2023::dayNN` API only.
+
+use std::fs;
+
+use aoc2023::{day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11};
+
+fn example(file: &str) -> String {
+    fs::read_to_string(format!("input/{}", file)).unwrap()
+}
+
+#[test]
+fn test_day01() {
+    let input = day01::parse(&example("day01_test01.txt")).unwrap();
+    assert_eq!(142, day01::part1(&input).unwrap());
+    let input = day01::parse(&example("day01_test02.txt")).unwrap();
+    assert_eq!(281, day01::part2(&input).unwrap());
+}
+
+#[test]
+fn test_day02() {
+    let input = day02::parse(&example("day02_test01.txt")).unwrap();
+    assert_eq!(8, day02::part1(&input).unwrap());
+    assert_eq!(2286, day02::part2(&input).unwrap());
+}
+
+#[test]
+fn test_day03() {
+    let input = day03::parse(&example("day03_test01.txt")).unwrap();
+    assert_eq!(4361, day03::part1(&input).unwrap());
+    assert_eq!(467835, day03::part2(&input).unwrap());
+}
+
+#[test]
+fn test_day04() {
+    let input = day04::parse(&example("day04_test01.txt")).unwrap();
+    assert_eq!(13, day04::part1(&input).unwrap());
+    assert_eq!(30, day04::part2(&input).unwrap());
+}
+
+#[test]
+fn test_day05() {
+    let input = day05::parse(&example("day05_test01.txt")).unwrap();
+    assert_eq!(35, day05::part1(&input).unwrap());
+    assert_eq!(46, day05::part2(&input).unwrap());
+}
+
+#[test]
+fn test_day06() {
+    let input = day06::parse(&example("day06_test01.txt")).unwrap();
+    assert_eq!(288, day06::part1(&input).unwrap());
+    assert_eq!(71503, day06::part2(&input).unwrap());
+}
+
+#[test]
+fn test_day07() {
+    let input = day07::parse(&example("day07_test01.txt")).unwrap();
+    assert_eq!(6440, day07::part1(&input).unwrap());
+    assert_eq!(5905, day07::part2(&input).unwrap());
+}
+
+#[test]
+fn test_day07_hand_types() {
+    let input = day07::parse("KTJJT 220\nQQQJA 483\n").unwrap();
+    let hands: &[day07::Hand] = &input.hands;
+    assert_eq!(day07::HandType::TwoPair, hands[0].hand_type());
+    assert_eq!(
+        day07::HandType::FourOfAKind,
+        hands[0].with_jokers().hand_type()
+    );
+    assert_eq!(day07::HandType::ThreeOfAKind, hands[1].hand_type());
+    assert_eq!(
+        vec![
+            day07::Card::Queen,
+            day07::Card::Queen,
+            day07::Card::Queen,
+            day07::Card::Jack,
+            day07::Card::Ace
+        ],
+        hands[1].cards
+    );
+    assert_eq!(483, hands[1].bid);
+}
+
+#[test]
+fn test_day08() {
+    let input = day08::parse(&example("day08_test01.txt")).unwrap();
+    assert_eq!(2, day08::part1(&input).unwrap());
+    let input = day08::parse(&example("day08_test03.txt")).unwrap();
+    assert_eq!(6, day08::part2(&input).unwrap());
+}
+
+#[test]
+fn test_day09() {
+    let input = day09::parse(&example("day09_test01.txt")).unwrap();
+    assert_eq!(18, day09::part1(&input).unwrap());
+    let input = day09::parse(&example("day09_test03.txt")).unwrap();
+    assert_eq!(5, day09::part2(&input).unwrap());
+}
+
+#[test]
+fn test_day10() {
+    let input = day10::parse(&example("day10_test01.txt")).unwrap();
+    assert_eq!(4, day10::part1(&input).unwrap());
+    assert_eq!(1, day10::part2(&input).unwrap());
+}
+
+#[test]
+fn test_day11() {
+    let input = day11::parse(&example("day11_test01.txt")).unwrap();
+    assert_eq!(374, day11::part1(&input).unwrap());
+}
+
+#[test]
+fn test_parse_file() {
+    let input = day07::parse_file("input/day07_test01.txt").unwrap();
+    assert_eq!(6440, day07::part1(&input).unwrap());
+    let err = day07::parse_file("input/missing.txt").unwrap_err();
+    assert!(matches!(err, aoc2023::Error::Io { ref path, .. } if path == "input/missing.txt"));
+}
+
+#[test]
+fn test_parse_error() {
+    let err = day07::parse("32T3K 765\nT55X5 684\n").unwrap_err();
+    assert!(matches!(err, aoc2023::Error::Parse { line: 2, .. }));
+}
+
+#[test]
+fn test_empty_submission_history() {
+    let history_file = std::env::temp_dir().join("aoc2023-days-no-history.txt");
+    let history: Vec<aoc2023::Record> = aoc2023::Submitter::new("secret")
+        .history_file(history_file)
+        .history()
+        .unwrap();
+    assert!(history.is_empty());
+}